@@ -84,7 +84,7 @@ pub enum AgentMemoryError {
     #[msg("Group description too long (max 256 characters)")]
     GroupDescTooLong,
     
-    #[msg("Group too large (max 50 members)")]
+    #[msg("Group too large")]
     GroupTooLarge,
     
     #[msg("Member already exists in group")]
@@ -140,4 +140,208 @@ pub enum AgentMemoryError {
     
     #[msg("Arithmetic overflow occurred")]
     ArithmeticOverflow,
+    
+    #[msg("Vault still holds memories")]
+    VaultNotEmpty,
+    
+    #[msg("Vault still holds staked tokens")]
+    VaultHasStake,
+    
+    #[msg("Vault still has active access grants")]
+    VaultHasActiveGrants,
+    
+    #[msg("Vault is frozen")]
+    VaultFrozen,
+    
+    #[msg("Invalid new owner")]
+    InvalidNewOwner,
+    
+    #[msg("No pending ownership transfer for this signer")]
+    NoPendingOwner,
+    
+    #[msg("Account has been migrated to a new agent key")]
+    AlreadyMigrated,
+    
+    #[msg("Signer is neither the vault owner nor an authorized delegate")]
+    UnauthorizedDelegate,
+    
+    #[msg("Delegate authorization expired")]
+    DelegateExpired,
+    
+    #[msg("Memory type not allowed for this delegate")]
+    DelegateScopeViolation,
+    
+    #[msg("Delegate daily write allowance exceeded")]
+    DelegateQuotaExceeded,
+    
+    #[msg("Vault storage quota exceeded")]
+    QuotaExceeded,
+    
+    #[msg("Invalid eviction candidate")]
+    InvalidEvictionCandidate,
+    
+    #[msg("Account is not a live memory shard of this vault")]
+    InvalidShardAccount,
+    
+    #[msg("Vault changed during reconciliation; restart it")]
+    ReconciliationStale,
+    
+    #[msg("Empty snapshot label not allowed")]
+    EmptySnapshotLabel,
+    
+    #[msg("Snapshot label too long (max 32 characters)")]
+    SnapshotLabelTooLong,
+    
+    #[msg("Too many approvers (max 10)")]
+    TooManyApprovers,
+    
+    #[msg("Invalid approval threshold")]
+    InvalidApprovalThreshold,
+    
+    #[msg("Duplicate approver")]
+    DuplicateApprover,
+    
+    #[msg("Signer is not a vault approver")]
+    NotApprover,
+    
+    #[msg("Approver already approved this proposal")]
+    AlreadyApproved,
+    
+    #[msg("Multisig approval required for this operation")]
+    MultisigApprovalRequired,
+    
+    #[msg("Proposal has expired")]
+    ProposalExpired,
+    
+    #[msg("Proposal already executed")]
+    ProposalAlreadyExecuted,
+    
+    #[msg("Proposal does not match this operation")]
+    ProposalActionMismatch,
+    
+    #[msg("Not enough approvals")]
+    InsufficientApprovals,
+    
+    #[msg("Proposal is still pending")]
+    ProposalStillPending,
+    
+    #[msg("Too many guardians (max 10)")]
+    TooManyGuardians,
+    
+    #[msg("Invalid guardian threshold")]
+    InvalidGuardianThreshold,
+    
+    #[msg("Guardian must be unique and not the vault owner")]
+    InvalidGuardian,
+    
+    #[msg("Invalid recovery delay (1 to 30 days)")]
+    InvalidRecoveryDelay,
+    
+    #[msg("Signer is not a vault guardian")]
+    NotGuardian,
+    
+    #[msg("Recovery veto delay has not elapsed")]
+    RecoveryDelayActive,
+    
+    #[msg("Invalid beneficiary")]
+    InvalidBeneficiary,
+    
+    #[msg("Invalid inactivity period (7 days to 5 years)")]
+    InvalidInactivityPeriod,
+    
+    #[msg("No inheritance configured for this beneficiary")]
+    InheritanceNotConfigured,
+    
+    #[msg("Owner has been active within the inactivity period")]
+    OwnerStillActive,
+    
+    #[msg("Account required by the inheritance mode is missing")]
+    MissingInheritanceAccount,
+    
+    #[msg("Access grant is still active")]
+    GrantStillActive,
+    
+    #[msg("Memory is outside the access grant scope")]
+    GrantScopeViolation,
+    
+    #[msg("Invalid grant scope")]
+    InvalidGrantScope,
+    
+    #[msg("Sharing group is inactive")]
+    GroupInactive,
+    
+    #[msg("Sharing group still has members")]
+    GroupNotEmpty,
+    
+    #[msg("Group key does not belong to the sharing group")]
+    InvalidGroupKey,
+    
+    #[msg("Group key epoch is outdated; rotate the group key")]
+    StaleGroupKey,
+    
+    #[msg("Too many wrapped keys in one instruction")]
+    TooManyWrappedKeys,
+    
+    #[msg("Sub-grant exceeds the permissions, scope or expiry of its parent grant")]
+    SubGrantExceedsParent,
+    
+    #[msg("Sub-grant chain too deep")]
+    GrantDepthExceeded,
+    
+    #[msg("Missing or invalid ancestor grant")]
+    InvalidGrantChain,
+    
+    #[msg("Grant was not issued under the signer's grant")]
+    NotGrantIssuer,
+    
+    #[msg("Access grant is not valid yet")]
+    AccessNotYetValid,
+    
+    #[msg("Invalid grant usage limit")]
+    InvalidMaxUses,
+    
+    #[msg("Sharing group still has open group key epochs")]
+    GroupKeysOpen,
+    
+    #[msg("Group key epoch is still in use")]
+    GroupKeyInUse,
+    
+    #[msg("Issued under a previous vault owner")]
+    StaleOwnerEpoch,
+    
+    #[msg("Recovery request has expired")]
+    RecoveryExpired,
+    
+    #[msg("Recovery request is still open")]
+    RecoveryStillOpen,
+    
+    #[msg("Account is not a memory vault of this program")]
+    InvalidVaultAccount,
+    
+    #[msg("Vault is not in a layout that can be migrated")]
+    UnsupportedVaultLayout,
+    
+    #[msg("No pending creator transfer")]
+    NoPendingCreator,
+    
+    #[msg("Invalid new group creator")]
+    InvalidNewCreator,
+    
+    #[msg("Account is not in a layout that can be migrated")]
+    UnsupportedAccountLayout,
+    
+    #[msg("Account does not belong to this vault")]
+    InvalidMigrationAccount,
+    
+    #[msg("Snapshot is sealed")]
+    SnapshotSealed,
+    
+    #[msg("Vault still has open delegates, grants, ACL entries, groups, snapshots, proposals or recovery requests")]
+    VaultHasOpenAccounts,
+    
+    #[msg("Reconciliation does not cover every shard of the vault")]
+    ReconciliationIncomplete,
+    
+    #[msg("Usage-limited grants cannot manage access or issue sub-grants")]
+    UsageLimitedGrant,
 }
@@ -0,0 +1,620 @@
+use anchor_lang::prelude::*;
+use crate::{AgentMemoryError, GroupMember, MemoryVault, SharingGroup};
+
+// ============================================================================
+// GROUP KEYS MODULE
+// ============================================================================
+// Shared encryption keys for sharing groups. The creator generates a group
+// key off-chain, wraps it to every member's X25519 encryption key and stores
+// the wrapped copies in a `GroupKeyEpoch` account. Once a group has a key,
+// content written through group membership must be encrypted under the
+// current epoch. Removing a member flags the group for rotation; until the
+// creator rotates to a fresh epoch, group members cannot write and writes
+// under the old key are rejected, so removed members cannot read new content.
+// Shards record the epoch account their content was encrypted under.
+
+/// Size of a wrapped group key: ephemeral X25519 public key, ciphertext and tag
+pub const WRAPPED_KEY_LENGTH: usize = 32 + 32 + 16;
+
+/// Maximum wrapped keys added per instruction (keeps reallocs under 10 KiB)
+pub const MAX_WRAPPED_KEYS_PER_IX: usize = 64;
+
+/// Group key wrapped to a single member
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct WrappedGroupKey {
+    pub member: Pubkey,
+    pub wrapped_key: [u8; WRAPPED_KEY_LENGTH],
+}
+
+/// One generation of a sharing group's encryption key
+#[account]
+pub struct GroupKeyEpoch {
+    pub group: Pubkey,
+    pub epoch: u32,
+    pub created_by: Pubkey,
+    pub created_at: i64,
+    /// Grows by realloc as members are added; one entry per member
+    pub wrapped_keys: Vec<WrappedGroupKey>,
+    pub bump: u8,
+}
+
+impl GroupKeyEpoch {
+    /// Account size (including discriminator) holding `wrapped_keys` entries
+    pub fn space(wrapped_keys: usize) -> usize {
+        8 + 32 + 4 + 32 + 8 + 4 + wrapped_keys * WrappedGroupKey::INIT_SPACE + 1
+    }
+}
+
+/// Checks that `group_key` is the current key of `group` and returns its address
+///
+/// Returns `None` when no group key is given (content encrypted to the vault
+/// key). Writes made through `membership` of a group that has a key must
+/// supply its current epoch.
+pub fn resolve_group_key(
+    group: Option<&Account<SharingGroup>>,
+    membership: Option<&GroupMember>,
+    group_key: Option<&Account<GroupKeyEpoch>>,
+) -> Result<Option<Pubkey>> {
+    let Some(group_key) = group_key else {
+        if let (Some(group), Some(_)) = (group, membership) {
+            require!(group.key_epoch == 0, AgentMemoryError::InvalidGroupKey);
+        }
+        return Ok(None);
+    };
+    let group = group.ok_or(AgentMemoryError::InvalidGroupKey)?;
+
+    require_keys_eq!(group_key.group, group.key(), AgentMemoryError::InvalidGroupKey);
+    require!(
+        group_key.epoch == group.key_epoch && !group.key_rotation_pending,
+        AgentMemoryError::StaleGroupKey
+    );
+
+    Ok(Some(group_key.key()))
+}
+
+// ============================================================================
+// INSTRUCTIONS
+// ============================================================================
+
+/// Start a new group key epoch
+///
+/// Clears the rotation flag set by member removal. `wrapped_keys` holds the
+/// first batch of member keys; the rest go through `add_wrapped_group_keys`.
+pub fn rotate_group_key(
+    ctx: Context<RotateGroupKey>,
+    wrapped_keys: Vec<WrappedGroupKey>,
+) -> Result<()> {
+    require!(
+        wrapped_keys.len() <= MAX_WRAPPED_KEYS_PER_IX,
+        AgentMemoryError::TooManyWrappedKeys
+    );
+
+    let clock = Clock::get()?;
+    let group = &mut ctx.accounts.sharing_group;
+    let group_key_epoch = &mut ctx.accounts.group_key_epoch;
+
+    let epoch = group
+        .key_epoch
+        .checked_add(1)
+        .ok_or(AgentMemoryError::Overflow)?;
+
+    group_key_epoch.group = group.key();
+    group_key_epoch.epoch = epoch;
+    group_key_epoch.created_by = ctx.accounts.owner.key();
+    group_key_epoch.created_at = clock.unix_timestamp;
+    group_key_epoch.wrapped_keys = wrapped_keys;
+    group_key_epoch.bump = ctx.bumps.group_key_epoch;
+
+    group.key_epoch = epoch;
+    group.open_key_epochs = group
+        .open_key_epochs
+        .checked_add(1)
+        .ok_or(AgentMemoryError::Overflow)?;
+    group.key_rotation_pending = false;
+    group.updated_at = clock.unix_timestamp;
+    ctx.accounts.vault.last_heartbeat = clock.unix_timestamp;
+
+    emit!(GroupKeyRotated {
+        group: group.key(),
+        group_key_epoch: group_key_epoch.key(),
+        epoch,
+        wrapped_keys: group_key_epoch.wrapped_keys.len() as u32,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Append member keys to the current epoch, e.g. for newly added members
+pub fn add_wrapped_group_keys(
+    ctx: Context<AddWrappedGroupKeys>,
+    wrapped_keys: Vec<WrappedGroupKey>,
+) -> Result<()> {
+    require!(
+        !wrapped_keys.is_empty() && wrapped_keys.len() <= MAX_WRAPPED_KEYS_PER_IX,
+        AgentMemoryError::TooManyWrappedKeys
+    );
+    require!(
+        ctx.accounts.group_key_epoch.epoch == ctx.accounts.sharing_group.key_epoch,
+        AgentMemoryError::StaleGroupKey
+    );
+
+    let clock = Clock::get()?;
+    let group_key_epoch = &mut ctx.accounts.group_key_epoch;
+    let added = wrapped_keys.len() as u32;
+
+    group_key_epoch.wrapped_keys.extend(wrapped_keys);
+    ctx.accounts.vault.last_heartbeat = clock.unix_timestamp;
+
+    emit!(GroupKeysWrapped {
+        group: group_key_epoch.group,
+        epoch: group_key_epoch.epoch,
+        added,
+        total: group_key_epoch.wrapped_keys.len() as u32,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Close a group key epoch and return its rent to the vault owner
+///
+/// Superseded epochs can be closed at any time; the current epoch only once
+/// the group is archived. Clients must keep any unwrapped keys still needed
+/// to read shards written under a closed epoch.
+pub fn close_group_key_epoch(ctx: Context<CloseGroupKeyEpoch>) -> Result<()> {
+    let group = &mut ctx.accounts.sharing_group;
+    let group_key_epoch = &ctx.accounts.group_key_epoch;
+
+    require!(
+        group_key_epoch.epoch < group.key_epoch || !group.is_active,
+        AgentMemoryError::GroupKeyInUse
+    );
+
+    let clock = Clock::get()?;
+    group.open_key_epochs = group.open_key_epochs.saturating_sub(1);
+    group.updated_at = clock.unix_timestamp;
+    ctx.accounts.vault.last_heartbeat = clock.unix_timestamp;
+
+    emit!(GroupKeyEpochClosed {
+        group: group.key(),
+        group_key_epoch: group_key_epoch.key(),
+        epoch: group_key_epoch.epoch,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// ============================================================================
+// CONTEXT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(wrapped_keys: Vec<WrappedGroupKey>)]
+pub struct RotateGroupKey<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+
+    #[account(
+        mut,
+        seeds = [b"group", vault.key().as_ref(), sharing_group.seed_name.as_bytes()],
+        bump = sharing_group.bump,
+        has_one = vault,
+    )]
+    pub sharing_group: Account<'info, SharingGroup>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = GroupKeyEpoch::space(wrapped_keys.len()),
+        seeds = [
+            b"group_key",
+            sharing_group.key().as_ref(),
+            &sharing_group.key_epoch.wrapping_add(1).to_le_bytes(),
+        ],
+        bump
+    )]
+    pub group_key_epoch: Account<'info, GroupKeyEpoch>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(wrapped_keys: Vec<WrappedGroupKey>)]
+pub struct AddWrappedGroupKeys<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+
+    #[account(
+        seeds = [b"group", vault.key().as_ref(), sharing_group.seed_name.as_bytes()],
+        bump = sharing_group.bump,
+        has_one = vault,
+    )]
+    pub sharing_group: Account<'info, SharingGroup>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"group_key",
+            sharing_group.key().as_ref(),
+            &group_key_epoch.epoch.to_le_bytes(),
+        ],
+        bump = group_key_epoch.bump,
+        realloc = GroupKeyEpoch::space(group_key_epoch.wrapped_keys.len() + wrapped_keys.len()),
+        realloc::payer = owner,
+        realloc::zero = false,
+    )]
+    pub group_key_epoch: Account<'info, GroupKeyEpoch>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseGroupKeyEpoch<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+
+    #[account(
+        mut,
+        seeds = [b"group", vault.key().as_ref(), sharing_group.seed_name.as_bytes()],
+        bump = sharing_group.bump,
+        has_one = vault,
+    )]
+    pub sharing_group: Account<'info, SharingGroup>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [
+            b"group_key",
+            sharing_group.key().as_ref(),
+            &group_key_epoch.epoch.to_le_bytes(),
+        ],
+        bump = group_key_epoch.bump,
+    )]
+    pub group_key_epoch: Account<'info, GroupKeyEpoch>,
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[event]
+pub struct GroupKeyRotated {
+    pub group: Pubkey,
+    pub group_key_epoch: Pubkey,
+    pub epoch: u32,
+    pub wrapped_keys: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GroupKeyEpochClosed {
+    pub group: Pubkey,
+    pub group_key_epoch: Pubkey,
+    pub epoch: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GroupKeysWrapped {
+    pub group: Pubkey,
+    pub epoch: u32,
+    pub added: u32,
+    pub total: u32,
+    pub timestamp: i64,
+}
+
+// ============================================================================
+// OFF-CHAIN HELPERS
+// ============================================================================
+
+/// Key generation and wrapping for clients (enable the `client` feature)
+///
+/// A wrapped key is `ephemeral_pubkey || ChaCha20-Poly1305(group_key)`, where
+/// the cipher key is derived from an X25519 exchange between a fresh
+/// ephemeral key and the member's encryption key. Each wrap uses a new
+/// ephemeral key, so the nonce is fixed.
+#[cfg(feature = "client")]
+pub mod client {
+    use super::{GroupKeyEpoch, WrappedGroupKey, WRAPPED_KEY_LENGTH};
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::solana_program::hash::hashv;
+    use chacha20poly1305::aead::{Aead, NewAead};
+    use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+    use rand::rngs::OsRng;
+    use rand::RngCore;
+    use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+
+    const KDF_DOMAIN: &[u8] = b"agent-memory/group-key/v1";
+    const NONCE: [u8; 12] = [0; 12];
+
+    fn wrapping_cipher(shared: &[u8; 32], ephemeral: &[u8; 32], member: &[u8; 32]) -> ChaCha20Poly1305 {
+        let key = hashv(&[KDF_DOMAIN, shared, ephemeral, member]).to_bytes();
+        ChaCha20Poly1305::new(Key::from_slice(&key))
+    }
+
+    /// Generates a random 32-byte group key
+    pub fn generate_group_key() -> [u8; 32] {
+        let mut key = [0u8; 32];
+        OsRng.fill_bytes(&mut key);
+        key
+    }
+
+    /// Wraps `group_key` to a member's X25519 encryption public key
+    pub fn wrap_group_key(group_key: &[u8; 32], member_pubkey: &[u8; 32]) -> [u8; WRAPPED_KEY_LENGTH] {
+        let ephemeral_secret = EphemeralSecret::new(OsRng);
+        let ephemeral_pubkey = PublicKey::from(&ephemeral_secret).to_bytes();
+        let shared = ephemeral_secret.diffie_hellman(&PublicKey::from(*member_pubkey));
+
+        let ciphertext = wrapping_cipher(shared.as_bytes(), &ephemeral_pubkey, member_pubkey)
+            .encrypt(Nonce::from_slice(&NONCE), group_key.as_ref())
+            .expect("encrypting 32 bytes cannot fail");
+
+        let mut wrapped = [0u8; WRAPPED_KEY_LENGTH];
+        wrapped[..32].copy_from_slice(&ephemeral_pubkey);
+        wrapped[32..].copy_from_slice(&ciphertext);
+        wrapped
+    }
+
+    /// Unwraps a group key with the member's X25519 encryption secret
+    ///
+    /// Returns `None` if the key was not wrapped to this secret or was tampered with.
+    pub fn unwrap_group_key(wrapped: &[u8; WRAPPED_KEY_LENGTH], member_secret: &[u8; 32]) -> Option<[u8; 32]> {
+        let secret = StaticSecret::from(*member_secret);
+        let member_pubkey = PublicKey::from(&secret).to_bytes();
+        let mut ephemeral_pubkey = [0u8; 32];
+        ephemeral_pubkey.copy_from_slice(&wrapped[..32]);
+        let shared = secret.diffie_hellman(&PublicKey::from(ephemeral_pubkey));
+
+        let plaintext = wrapping_cipher(shared.as_bytes(), &ephemeral_pubkey, &member_pubkey)
+            .decrypt(Nonce::from_slice(&NONCE), &wrapped[32..])
+            .ok()?;
+        plaintext.try_into().ok()
+    }
+
+    /// Wraps `group_key` for each `(member, encryption pubkey)` pair
+    pub fn wrap_for_members(group_key: &[u8; 32], members: &[(Pubkey, [u8; 32])]) -> Vec<WrappedGroupKey> {
+        members
+            .iter()
+            .map(|(member, pubkey)| WrappedGroupKey {
+                member: *member,
+                wrapped_key: wrap_group_key(group_key, pubkey),
+            })
+            .collect()
+    }
+
+    /// Finds and unwraps `member`'s copy of an epoch's group key
+    pub fn unwrap_for_member(
+        epoch: &GroupKeyEpoch,
+        member: &Pubkey,
+        member_secret: &[u8; 32],
+    ) -> Option<[u8; 32]> {
+        epoch
+            .wrapped_keys
+            .iter()
+            .rev()
+            .filter(|entry| entry.member == *member)
+            .find_map(|entry| unwrap_group_key(&entry.wrapped_key, member_secret))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn member_keypair() -> ([u8; 32], [u8; 32]) {
+            let secret = StaticSecret::new(OsRng);
+            (secret.to_bytes(), PublicKey::from(&secret).to_bytes())
+        }
+
+        fn epoch(wrapped_keys: Vec<WrappedGroupKey>) -> GroupKeyEpoch {
+            GroupKeyEpoch {
+                group: Pubkey::new_unique(),
+                epoch: 1,
+                created_by: Pubkey::new_unique(),
+                created_at: 0,
+                wrapped_keys,
+                bump: 255,
+            }
+        }
+
+        #[test]
+        fn wrapped_key_round_trips() {
+            let group_key = generate_group_key();
+            let (secret, pubkey) = member_keypair();
+
+            let wrapped = wrap_group_key(&group_key, &pubkey);
+
+            assert_eq!(unwrap_group_key(&wrapped, &secret), Some(group_key));
+        }
+
+        #[test]
+        fn wrapping_is_randomized() {
+            let group_key = generate_group_key();
+            let (_, pubkey) = member_keypair();
+
+            assert_ne!(wrap_group_key(&group_key, &pubkey), wrap_group_key(&group_key, &pubkey));
+        }
+
+        #[test]
+        fn other_secret_cannot_unwrap() {
+            let group_key = generate_group_key();
+            let (_, pubkey) = member_keypair();
+            let (other_secret, _) = member_keypair();
+
+            let wrapped = wrap_group_key(&group_key, &pubkey);
+
+            assert_eq!(unwrap_group_key(&wrapped, &other_secret), None);
+        }
+
+        #[test]
+        fn tampered_key_is_rejected() {
+            let group_key = generate_group_key();
+            let (secret, pubkey) = member_keypair();
+            let wrapped = wrap_group_key(&group_key, &pubkey);
+
+            for index in [0, 32, WRAPPED_KEY_LENGTH - 1] {
+                let mut tampered = wrapped;
+                tampered[index] ^= 1;
+                assert_eq!(unwrap_group_key(&tampered, &secret), None);
+            }
+        }
+
+        #[test]
+        fn unwraps_member_entry() {
+            let group_key = generate_group_key();
+            let (alice_secret, alice_pubkey) = member_keypair();
+            let (bob_secret, bob_pubkey) = member_keypair();
+            let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+            let epoch = epoch(wrap_for_members(&group_key, &[(alice, alice_pubkey), (bob, bob_pubkey)]));
+
+            assert_eq!(unwrap_for_member(&epoch, &alice, &alice_secret), Some(group_key));
+            assert_eq!(unwrap_for_member(&epoch, &bob, &bob_secret), Some(group_key));
+            assert_eq!(unwrap_for_member(&epoch, &bob, &alice_secret), None);
+            assert_eq!(unwrap_for_member(&epoch, &Pubkey::new_unique(), &alice_secret), None);
+        }
+
+        #[test]
+        fn skips_stale_member_entries() {
+            let group_key = generate_group_key();
+            let (old_secret, old_pubkey) = member_keypair();
+            let (secret, pubkey) = member_keypair();
+            let member = Pubkey::new_unique();
+            let epoch = epoch(wrap_for_members(&group_key, &[(member, old_pubkey), (member, pubkey)]));
+
+            assert_eq!(unwrap_for_member(&epoch, &member, &secret), Some(group_key));
+            assert_eq!(unwrap_for_member(&epoch, &member, &old_secret), Some(group_key));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::account;
+
+    fn group(key_epoch: u32) -> SharingGroup {
+        SharingGroup {
+            creator: Pubkey::new_unique(),
+            pending_creator: None,
+            vault: Pubkey::new_unique(),
+            seed_name: "team".to_string(),
+            name: "team".to_string(),
+            description: String::new(),
+            member_count: 1,
+            key_epoch,
+            open_key_epochs: key_epoch.min(1),
+            key_rotation_pending: false,
+            created_at: 0,
+            updated_at: 0,
+            is_active: true,
+            owner_epoch: 0,
+            bump: 255,
+        }
+    }
+
+    fn member(group: &Account<SharingGroup>) -> GroupMember {
+        GroupMember {
+            group: group.key(),
+            member: Pubkey::new_unique(),
+            permission: crate::PermissionLevel::Write,
+            permissions: crate::PERMISSION_READ | crate::PERMISSION_CREATE,
+            joined_at: 0,
+            payer: Pubkey::new_unique(),
+            bump: 255,
+        }
+    }
+
+    fn key_epoch(group: &Account<SharingGroup>, epoch: u32) -> GroupKeyEpoch {
+        GroupKeyEpoch {
+            group: group.key(),
+            epoch,
+            created_by: group.creator,
+            created_at: 0,
+            wrapped_keys: vec![],
+            bump: 255,
+        }
+    }
+
+    fn error_code<T>(result: Result<T>) -> u32 {
+        match result {
+            Err(Error::AnchorError(error)) => error.error_code_number,
+            _ => panic!("expected an Anchor error"),
+        }
+    }
+
+    #[test]
+    fn accepts_current_key() {
+        let group = account(&group(2));
+        let membership = member(&group);
+        let current = account(&key_epoch(&group, 2));
+
+        let resolved = resolve_group_key(Some(&group), Some(&membership), Some(&current)).unwrap();
+
+        assert_eq!(resolved, Some(current.key()));
+    }
+
+    #[test]
+    fn key_is_optional_until_group_has_one() {
+        let unkeyed = account(&group(0));
+        let keyed = account(&group(1));
+
+        assert_eq!(resolve_group_key(Some(&unkeyed), Some(&member(&unkeyed)), None).unwrap(), None);
+        assert_eq!(resolve_group_key(Some(&keyed), None, None).unwrap(), None);
+        assert_eq!(resolve_group_key(None, None, None).unwrap(), None);
+        assert_eq!(
+            error_code(resolve_group_key(Some(&keyed), Some(&member(&keyed)), None)),
+            u32::from(AgentMemoryError::InvalidGroupKey)
+        );
+    }
+
+    #[test]
+    fn rejects_key_of_other_group() {
+        let group_a = account(&group(1));
+        let group_b = account(&group(1));
+        let key_b = account(&key_epoch(&group_b, 1));
+        let invalid = u32::from(AgentMemoryError::InvalidGroupKey);
+
+        assert_eq!(error_code(resolve_group_key(Some(&group_a), Some(&member(&group_a)), Some(&key_b))), invalid);
+        assert_eq!(error_code(resolve_group_key(None, None, Some(&key_b))), invalid);
+    }
+
+    #[test]
+    fn rejects_old_key_and_pending_rotation() {
+        let group = account(&group(2));
+        let old = account(&key_epoch(&group, 1));
+        let stale = u32::from(AgentMemoryError::StaleGroupKey);
+
+        assert_eq!(error_code(resolve_group_key(Some(&group), Some(&member(&group)), Some(&old))), stale);
+
+        let mut rotating = group.clone();
+        rotating.key_rotation_pending = true;
+        let current = account(&key_epoch(&rotating, 2));
+        assert_eq!(error_code(resolve_group_key(Some(&rotating), Some(&member(&rotating)), Some(&current))), stale);
+    }
+}
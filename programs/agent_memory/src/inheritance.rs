@@ -0,0 +1,326 @@
+use anchor_lang::prelude::*;
+use crate::{
+    require_approval, AccessGrant, AccessGranted, AgentMemoryError, AgentProfile, GrantScope,
+    MemoryVault, PermissionLevel,
+    PERMISSION_ALL, ProposalAction, VaultProposal,
+};
+
+// ============================================================================
+// INHERITANCE MODULE
+// ============================================================================
+// Dead-man switch for long-running agents. The owner names a beneficiary and
+// an inactivity period; every owner-signed vault mutation (or an explicit
+// `heartbeat`) resets the timer. Once the period lapses anyone may call
+// `claim_inheritance`, which either grants the beneficiary Admin access or
+// hands them the vault, depending on the configured mode.
+
+/// Minimum inactivity period before inheritance can be claimed (7 days)
+pub const MIN_INACTIVITY_PERIOD: i64 = 7 * 24 * 60 * 60;
+
+/// Maximum inactivity period before inheritance can be claimed (5 years)
+pub const MAX_INACTIVITY_PERIOD: i64 = 5 * 365 * 24 * 60 * 60;
+
+/// What the beneficiary receives when inheritance is claimed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum InheritanceMode {
+    /// Beneficiary receives a non-expiring Admin access grant
+    GrantAdmin,
+    /// Beneficiary becomes the vault owner
+    TransferOwnership,
+}
+
+// ============================================================================
+// INSTRUCTIONS
+// ============================================================================
+
+/// Configure or disarm the inactivity switch
+///
+/// Passing `None` as beneficiary disarms the switch. Configuring it also
+/// counts as a heartbeat.
+pub fn set_inheritance(
+    ctx: Context<SetInheritance>,
+    beneficiary: Option<Pubkey>,
+    inactivity_period: i64,
+    mode: InheritanceMode,
+) -> Result<()> {
+    let owner = ctx.accounts.owner.key();
+
+    if let Some(beneficiary) = beneficiary {
+        require!(
+            beneficiary != owner && beneficiary != Pubkey::default(),
+            AgentMemoryError::InvalidBeneficiary
+        );
+        require!(
+            (MIN_INACTIVITY_PERIOD..=MAX_INACTIVITY_PERIOD).contains(&inactivity_period),
+            AgentMemoryError::InvalidInactivityPeriod
+        );
+    }
+
+    let clock = Clock::get()?;
+
+    // The beneficiary may end up with full control, so this needs multisig approval
+    require_approval(
+        &ctx.accounts.vault,
+        ctx.accounts.proposal.as_mut(),
+        &ProposalAction::SetInheritance {
+            beneficiary,
+            inactivity_period,
+            mode,
+        },
+        owner,
+        clock.unix_timestamp,
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    vault.beneficiary = beneficiary;
+    vault.inactivity_period = if beneficiary.is_some() { inactivity_period } else { 0 };
+    vault.inheritance_mode = mode;
+    vault.last_heartbeat = clock.unix_timestamp;
+    vault.updated_at = clock.unix_timestamp;
+
+    emit!(InheritanceConfigured {
+        vault: vault.key(),
+        beneficiary,
+        inactivity_period: vault.inactivity_period,
+        mode,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Signal that the owner is still active
+pub fn heartbeat(ctx: Context<Heartbeat>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let clock = Clock::get()?;
+
+    vault.last_heartbeat = clock.unix_timestamp;
+
+    emit!(OwnerHeartbeat {
+        vault: vault.key(),
+        owner: vault.owner,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Claim a vault's inheritance once the owner has been inactive too long
+///
+/// Permissionless. In `GrantAdmin` mode `access_grant` must be supplied; in
+/// `TransferOwnership` mode `agent_profile` must be supplied. The switch is
+/// disarmed afterwards so the owner (or new owner) must re-arm it.
+pub fn claim_inheritance(ctx: Context<ClaimInheritance>) -> Result<()> {
+    let clock = Clock::get()?;
+    let vault = &mut ctx.accounts.vault;
+    let beneficiary = ctx.accounts.beneficiary.key();
+
+    require!(
+        vault.beneficiary == Some(beneficiary),
+        AgentMemoryError::InheritanceNotConfigured
+    );
+    let claimable_at = vault
+        .last_heartbeat
+        .checked_add(vault.inactivity_period)
+        .ok_or(AgentMemoryError::Overflow)?;
+    require!(
+        clock.unix_timestamp >= claimable_at,
+        AgentMemoryError::OwnerStillActive
+    );
+
+    let previous_owner = vault.owner;
+    let mode = vault.inheritance_mode;
+
+    match mode {
+        InheritanceMode::GrantAdmin => {
+            let access_grant = ctx
+                .accounts
+                .access_grant
+                .as_mut()
+                .ok_or(AgentMemoryError::MissingInheritanceAccount)?;
+
+            if !access_grant.is_active {
+                vault.active_grant_count = vault
+                    .active_grant_count
+                    .checked_add(1)
+                    .ok_or(AgentMemoryError::Overflow)?;
+            }
+
+            access_grant.vault = vault.key();
+            access_grant.grantee = beneficiary;
+            access_grant.permission_level = PermissionLevel::Admin;
+            access_grant.permissions = PERMISSION_ALL;
+            access_grant.granted_at = clock.unix_timestamp;
+            access_grant.expires_at = None;
+            access_grant.not_before = None;
+            access_grant.remaining_uses = None;
+            access_grant.is_active = true;
+            access_grant.revoked_at = None;
+            access_grant.scope = GrantScope::default();
+            access_grant.parent_grant = None;
+            access_grant.parent_granted_at = 0;
+            access_grant.depth = 0;
+            access_grant.owner_epoch = vault.owner_epoch;
+            if access_grant.payer == Pubkey::default() {
+                access_grant.payer = ctx.accounts.payer.key();
+                vault.track_open_account()?;
+            }
+            access_grant.bump = ctx.bumps.access_grant.unwrap_or_default();
+
+            emit!(AccessGranted {
+                vault: vault.key(),
+                grantee: beneficiary,
+                granted_by: previous_owner,
+                permission_level: PermissionLevel::Admin as u8,
+                permissions: PERMISSION_ALL,
+                granted_at: clock.unix_timestamp,
+                expires_at: None,
+                not_before: None,
+                max_uses: None,
+                scope: GrantScope::default(),
+                parent_grant: None,
+            });
+        }
+        InheritanceMode::TransferOwnership => {
+            let agent_profile = ctx
+                .accounts
+                .agent_profile
+                .as_mut()
+                .ok_or(AgentMemoryError::MissingInheritanceAccount)?;
+
+            vault.owner = beneficiary;
+            vault.pending_owner = None;
+            vault.owner_epoch = vault
+                .owner_epoch
+                .checked_add(1)
+                .ok_or(AgentMemoryError::Overflow)?;
+            vault.last_heartbeat = clock.unix_timestamp;
+
+            agent_profile.owner = beneficiary;
+            agent_profile.updated_at = clock.unix_timestamp;
+        }
+    }
+
+    vault.beneficiary = None;
+    vault.inactivity_period = 0;
+    vault.updated_at = clock.unix_timestamp;
+
+    emit!(InheritanceClaimed {
+        vault: vault.key(),
+        beneficiary,
+        previous_owner,
+        mode,
+        claimed_by: ctx.accounts.payer.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// ============================================================================
+// CONTEXT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct SetInheritance<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+
+    /// Approved proposal, required when the vault has an approver set
+    #[account(
+        mut,
+        seeds = [b"proposal", vault.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Option<Account<'info, VaultProposal>>,
+}
+
+#[derive(Accounts)]
+pub struct Heartbeat<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimInheritance<'info> {
+    /// Pays for the access grant when one is created
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        constraint = vault.migrated_to.is_none() @ AgentMemoryError::AlreadyMigrated,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+
+    /// CHECK: Must match the beneficiary configured on the vault
+    pub beneficiary: AccountInfo<'info>,
+
+    /// Beneficiary's grant, required in `GrantAdmin` mode
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + AccessGrant::INIT_SPACE,
+        seeds = [b"access", vault.key().as_ref(), beneficiary.key().as_ref()],
+        bump
+    )]
+    pub access_grant: Option<Account<'info, AccessGrant>>,
+
+    /// Vault's agent profile, required in `TransferOwnership` mode
+    #[account(
+        mut,
+        seeds = [b"profile", vault.agent_key.as_ref()],
+        bump = agent_profile.bump,
+        has_one = vault,
+    )]
+    pub agent_profile: Option<Account<'info, AgentProfile>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[event]
+pub struct InheritanceConfigured {
+    pub vault: Pubkey,
+    pub beneficiary: Option<Pubkey>,
+    pub inactivity_period: i64,
+    pub mode: InheritanceMode,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OwnerHeartbeat {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InheritanceClaimed {
+    pub vault: Pubkey,
+    pub beneficiary: Pubkey,
+    pub previous_owner: Pubkey,
+    pub mode: InheritanceMode,
+    pub claimed_by: Pubkey,
+    pub timestamp: i64,
+}
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
 use anchor_spl::token::{self, Token, TokenAccount};
 use crate::*;
 
@@ -30,14 +31,38 @@ pub fn initialize_vault(
     
     // Initialize vault
     vault.owner = owner.key();
+    vault.layout_version = VAULT_LAYOUT_VERSION;
+    vault.seed_owner = owner.key();
+    vault.pending_owner = None;
+    vault.owner_epoch = 0;
     vault.agent_key = agent_key.key();
     vault.encryption_pubkey = encryption_pubkey;
     vault.created_at = clock.unix_timestamp;
     vault.updated_at = clock.unix_timestamp;
     vault.memory_count = 0;
     vault.total_memory_size = 0;
+    vault.deleted_memory_count = 0;
+    vault.shards_created = 0;
+    vault.shards_closed = 0;
     vault.staked_amount = 0;
     vault.reward_points = 0;
+    vault.active_grant_count = 0;
+    vault.open_account_count = 0;
+    vault.max_memory_count = 0;
+    vault.max_total_bytes = 0;
+    vault.eviction_enabled = false;
+    vault.approvers = Vec::new();
+    vault.approval_threshold = 0;
+    vault.proposal_count = 0;
+    vault.guardians = Vec::new();
+    vault.guardian_threshold = 0;
+    vault.recovery_delay = 0;
+    vault.beneficiary = None;
+    vault.inactivity_period = 0;
+    vault.inheritance_mode = InheritanceMode::GrantAdmin;
+    vault.last_heartbeat = clock.unix_timestamp;
+    vault.memory_root = vault.key();
+    vault.migrated_to = None;
     vault.is_active = true;
     vault.bump = ctx.bumps.vault;
     
@@ -53,6 +78,7 @@ pub fn initialize_vault(
     agent_profile.updated_at = clock.unix_timestamp;
     agent_profile.last_task_at = 0;
     agent_profile.is_public = false;
+    agent_profile.migrated_to = None;
     agent_profile.bump = ctx.bumps.agent_profile;
     
     emit!(VaultInitialized {
@@ -66,12 +92,1591 @@ pub fn initialize_vault(
     Ok(())
 }
 
+/// Closes a vault and its agent profile, returning rent to the owner
+/// 
+/// # Arguments
+/// * `ctx` - Context containing vault and profile accounts
+/// 
+/// # Errors
+/// * `VaultNotEmpty` - If the vault still holds memories, live or soft-deleted
+/// * `VaultHasStake` - If the vault still holds staked tokens
+/// * `VaultHasActiveGrants` - If any access grant is still active
+/// * `VaultHasOpenAccounts` - If any delegate, grant, ACL entry, group,
+///   snapshot, proposal or recovery request is still open
+pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    
+    require!(
+        vault.memory_count == 0
+            && vault.deleted_memory_count == 0
+            && vault.shards_created == vault.shards_closed,
+        AgentMemoryError::VaultNotEmpty
+    );
+    require!(vault.staked_amount == 0, AgentMemoryError::VaultHasStake);
+    require!(
+        vault.active_grant_count == 0,
+        AgentMemoryError::VaultHasActiveGrants
+    );
+    require!(
+        vault.open_account_count == 0,
+        AgentMemoryError::VaultHasOpenAccounts
+    );
+    
+    let clock = Clock::get()?;
+    
+    emit!(VaultClosed {
+        vault: vault.key(),
+        profile: ctx.accounts.agent_profile.key(),
+        owner: vault.owner,
+        agent_key: vault.agent_key,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Freezes or unfreezes a vault
+/// 
+/// A frozen vault rejects every memory and grant mutation until the owner
+/// reactivates it; revoking access remains possible while frozen.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing vault account
+/// * `active` - New vault state (`false` freezes the vault)
+/// 
+/// # Errors
+/// * `AlreadyMigrated` - If the vault has been migrated to a new agent key
+pub fn set_vault_active(ctx: Context<SetVaultActive>, active: bool) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let clock = Clock::get()?;
+    
+    // A migrated vault stays frozen; its memories belong to the successor
+    require!(vault.migrated_to.is_none(), AgentMemoryError::AlreadyMigrated);
+    
+    vault.is_active = active;
+    vault.updated_at = clock.unix_timestamp;
+    vault.last_heartbeat = clock.unix_timestamp;
+    
+    emit!(VaultActiveChanged {
+        vault: vault.key(),
+        owner: vault.owner,
+        is_active: active,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Proposes a new vault owner
+/// 
+/// The transfer only takes effect once `new_owner` calls `accept_vault_owner`.
+/// The vault address is unchanged because its PDA is derived from `seed_owner`.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing vault account
+/// * `new_owner` - Proposed owner public key
+/// 
+/// # Errors
+/// * `InvalidNewOwner` - If `new_owner` is the current owner
+/// * `MultisigApprovalRequired` - If the vault has approvers and no approved proposal is given
+pub fn propose_vault_owner(ctx: Context<ProposeVaultOwner>, new_owner: Pubkey) -> Result<()> {
+    let clock = Clock::get()?;
+    
+    require!(
+        new_owner != ctx.accounts.vault.owner && new_owner != Pubkey::default(),
+        AgentMemoryError::InvalidNewOwner
+    );
+    
+    // Ownership transfer needs multisig approval when configured
+    require_approval(
+        &ctx.accounts.vault,
+        ctx.accounts.proposal.as_mut(),
+        &ProposalAction::TransferOwnership { new_owner },
+        ctx.accounts.owner.key(),
+        clock.unix_timestamp,
+    )?;
+    
+    let vault = &mut ctx.accounts.vault;
+    
+    vault.pending_owner = Some(new_owner);
+    vault.updated_at = clock.unix_timestamp;
+    vault.last_heartbeat = clock.unix_timestamp;
+    
+    emit!(VaultOwnerProposed {
+        vault: vault.key(),
+        owner: vault.owner,
+        pending_owner: vault.pending_owner,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Cancels a pending vault ownership transfer
+/// 
+/// # Arguments
+/// * `ctx` - Context containing vault account
+/// 
+/// # Errors
+/// * `NoPendingOwner` - If no transfer is pending
+pub fn cancel_vault_owner_transfer(ctx: Context<ProposeVaultOwner>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let clock = Clock::get()?;
+    
+    require!(vault.pending_owner.is_some(), AgentMemoryError::NoPendingOwner);
+    
+    vault.pending_owner = None;
+    vault.updated_at = clock.unix_timestamp;
+    vault.last_heartbeat = clock.unix_timestamp;
+    
+    emit!(VaultOwnerProposed {
+        vault: vault.key(),
+        owner: vault.owner,
+        pending_owner: None,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Accepts a pending vault ownership transfer
+/// 
+/// Moves both the vault and its agent profile to the new owner. Grants, ACL
+/// entries, delegates and sharing groups issued under the previous owner stop
+/// working until the new owner re-issues or reactivates them.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing vault and profile accounts
+pub fn accept_vault_owner(ctx: Context<AcceptVaultOwner>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let agent_profile = &mut ctx.accounts.agent_profile;
+    let new_owner = ctx.accounts.new_owner.key();
+    let old_owner = vault.owner;
+    let clock = Clock::get()?;
+    
+    vault.owner = new_owner;
+    vault.pending_owner = None;
+    vault.owner_epoch = vault
+        .owner_epoch
+        .checked_add(1)
+        .ok_or(AgentMemoryError::Overflow)?;
+    vault.updated_at = clock.unix_timestamp;
+    vault.last_heartbeat = clock.unix_timestamp;
+    
+    agent_profile.owner = new_owner;
+    agent_profile.updated_at = clock.unix_timestamp;
+    
+    emit!(VaultOwnerTransferred {
+        vault: vault.key(),
+        profile: agent_profile.key(),
+        old_owner,
+        new_owner,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Migrates a vault and agent profile to a new agent key
+/// 
+/// Creates the vault and profile PDAs for `new_agent_key`, carrying over
+/// counters and reputation. Memory shards stay where they are: they are
+/// derived from `memory_root`, which the new vault inherits. The old vault
+/// and profile are frozen and keep a `migrated_to` pointer for clients.
+/// Access grants are keyed by vault address and must be re-issued.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing old and new vault and profile accounts
+/// 
+/// # Errors
+/// * `AlreadyMigrated` - If the vault has already been migrated
+/// * `VaultHasStake` - If the vault still holds staked tokens
+pub fn migrate_agent_key(ctx: Context<MigrateAgentKey>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let agent_profile = &mut ctx.accounts.agent_profile;
+    let new_vault = &mut ctx.accounts.new_vault;
+    let new_profile = &mut ctx.accounts.new_agent_profile;
+    let new_agent_key = ctx.accounts.new_agent_key.key();
+    let clock = Clock::get()?;
+    
+    // Staked tokens are held under the old vault's authority
+    require!(vault.staked_amount == 0, AgentMemoryError::VaultHasStake);
+    
+    // Initialize successor vault
+    new_vault.owner = vault.owner;
+    new_vault.layout_version = VAULT_LAYOUT_VERSION;
+    new_vault.seed_owner = vault.owner;
+    new_vault.pending_owner = None;
+    new_vault.owner_epoch = 0;
+    new_vault.agent_key = new_agent_key;
+    new_vault.encryption_pubkey = vault.encryption_pubkey;
+    new_vault.created_at = vault.created_at;
+    new_vault.updated_at = clock.unix_timestamp;
+    new_vault.memory_count = vault.memory_count;
+    new_vault.total_memory_size = vault.total_memory_size;
+    new_vault.deleted_memory_count = vault.deleted_memory_count;
+    new_vault.shards_created = vault.shards_created;
+    new_vault.shards_closed = vault.shards_closed;
+    new_vault.staked_amount = 0;
+    new_vault.reward_points = vault.reward_points;
+    new_vault.active_grant_count = 0;
+    new_vault.open_account_count = 0;
+    new_vault.max_memory_count = vault.max_memory_count;
+    new_vault.max_total_bytes = vault.max_total_bytes;
+    new_vault.eviction_enabled = vault.eviction_enabled;
+    new_vault.approvers = vault.approvers.clone();
+    new_vault.approval_threshold = vault.approval_threshold;
+    new_vault.proposal_count = 0;
+    new_vault.guardians = vault.guardians.clone();
+    new_vault.guardian_threshold = vault.guardian_threshold;
+    new_vault.recovery_delay = vault.recovery_delay;
+    new_vault.beneficiary = vault.beneficiary;
+    new_vault.inactivity_period = vault.inactivity_period;
+    new_vault.inheritance_mode = vault.inheritance_mode;
+    new_vault.last_heartbeat = clock.unix_timestamp;
+    new_vault.memory_root = vault.memory_root;
+    new_vault.migrated_to = None;
+    new_vault.is_active = true;
+    new_vault.bump = ctx.bumps.new_vault;
+    
+    // Initialize successor profile
+    new_profile.agent_key = new_agent_key;
+    new_profile.owner = agent_profile.owner;
+    new_profile.vault = new_vault.key();
+    new_profile.name = agent_profile.name.clone();
+    new_profile.capabilities = agent_profile.capabilities.clone();
+    new_profile.reputation_score = agent_profile.reputation_score;
+    new_profile.tasks_completed = agent_profile.tasks_completed;
+    new_profile.created_at = agent_profile.created_at;
+    new_profile.updated_at = clock.unix_timestamp;
+    new_profile.last_task_at = agent_profile.last_task_at;
+    new_profile.is_public = agent_profile.is_public;
+    new_profile.migrated_to = None;
+    new_profile.bump = ctx.bumps.new_agent_profile;
+    
+    // Leave forwarding records at the old addresses
+    vault.migrated_to = Some(new_vault.key());
+    vault.pending_owner = None;
+    vault.is_active = false;
+    vault.updated_at = clock.unix_timestamp;
+    
+    agent_profile.migrated_to = Some(new_profile.key());
+    agent_profile.is_public = false;
+    agent_profile.updated_at = clock.unix_timestamp;
+    
+    emit!(AgentKeyMigrated {
+        owner: vault.owner,
+        old_vault: vault.key(),
+        new_vault: new_vault.key(),
+        old_profile: agent_profile.key(),
+        new_profile: new_profile.key(),
+        old_agent_key: vault.agent_key,
+        new_agent_key,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Resizes an account being migrated to a new layout
+/// 
+/// `payer` tops up the rent of a growing account and gets back the surplus
+/// rent of a shrinking one.
+fn resize_for_migration<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+) -> Result<()> {
+    let required = Rent::get()?.minimum_balance(space);
+    let balance = account.lamports();
+    
+    if required > balance {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            required - balance,
+        )?;
+    } else if space < account.data_len() {
+        let surplus = balance - required;
+        **account.try_borrow_mut_lamports()? -= surplus;
+        **payer.try_borrow_mut_lamports()? += surplus;
+    }
+    account.realloc(space, true)?;
+    
+    Ok(())
+}
+
+/// Reads an account of this program that is still in the legacy layout `T`
+/// 
+/// `discriminator` is the current account type's; `invalid` is returned for
+/// accounts of another program or type.
+fn read_legacy_account<T: AnchorDeserialize>(
+    account: &AccountInfo,
+    discriminator: [u8; 8],
+    legacy_space: usize,
+    invalid: AgentMemoryError,
+) -> Result<T> {
+    require_keys_eq!(*account.owner, crate::ID, invalid);
+    let data = account.try_borrow_data()?;
+    if data.len() < 8 || data[..8] != discriminator {
+        return Err(invalid.into());
+    }
+    require!(
+        data.len() == 8 + legacy_space,
+        AgentMemoryError::UnsupportedAccountLayout
+    );
+    Ok(T::deserialize(&mut &data[8..])?)
+}
+
+/// Migrates a vault created before layout versioning to the current layout
+/// 
+/// Legacy vaults can't be loaded as `MemoryVault`, so the vault is taken as
+/// a raw account and recognised by its program owner, discriminator, size
+/// and PDA. The account is grown to the current size, with the owner paying
+/// any extra rent, and every field added since is set to its default. Legacy
+/// usage counters could drift from the shards that exist, so they restart at
+/// zero and are rebuilt as `migrate_memory_layout` brings each shard over.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing the owner and the raw vault account
+/// 
+/// # Errors
+/// * `InvalidVaultAccount` - If the account is not a vault PDA of this program
+/// * `UnsupportedVaultLayout` - If the vault is not in the legacy layout
+/// * `UnauthorizedOwner` - If the signer does not own the vault
+pub fn migrate_vault_layout(ctx: Context<MigrateVaultLayout>) -> Result<()> {
+    let vault_info = ctx.accounts.vault.to_account_info();
+    let owner = ctx.accounts.owner.key();
+    let clock = Clock::get()?;
+    
+    require_keys_eq!(*vault_info.owner, crate::ID, AgentMemoryError::InvalidVaultAccount);
+    let legacy = {
+        let data = vault_info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == MemoryVault::DISCRIMINATOR,
+            AgentMemoryError::InvalidVaultAccount
+        );
+        require!(
+            data.len() == 8 + LegacyMemoryVault::INIT_SPACE,
+            AgentMemoryError::UnsupportedVaultLayout
+        );
+        LegacyMemoryVault::deserialize(&mut &data[8..])?
+    };
+    
+    require_keys_eq!(legacy.owner, owner, AgentMemoryError::UnauthorizedOwner);
+    let expected_vault = Pubkey::create_program_address(
+        &[b"vault", legacy.owner.as_ref(), legacy.agent_key.as_ref(), &[legacy.bump]],
+        &crate::ID,
+    )
+    .map_err(|_| AgentMemoryError::InvalidVaultAccount)?;
+    require_keys_eq!(expected_vault, vault_info.key(), AgentMemoryError::InvalidVaultAccount);
+    
+    resize_for_migration(
+        &vault_info,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        8 + MemoryVault::INIT_SPACE,
+    )?;
+    
+    let vault = MemoryVault {
+        owner: legacy.owner,
+        layout_version: VAULT_LAYOUT_VERSION,
+        seed_owner: legacy.owner,
+        pending_owner: None,
+        owner_epoch: 0,
+        agent_key: legacy.agent_key,
+        encryption_pubkey: legacy.encryption_pubkey,
+        created_at: legacy.created_at,
+        updated_at: clock.unix_timestamp,
+        memory_count: 0,
+        total_memory_size: 0,
+        deleted_memory_count: 0,
+        shards_created: 0,
+        shards_closed: 0,
+        staked_amount: legacy.staked_amount,
+        reward_points: legacy.reward_points,
+        active_grant_count: 0,
+        open_account_count: 0,
+        max_memory_count: 0,
+        max_total_bytes: 0,
+        eviction_enabled: false,
+        approvers: Vec::new(),
+        approval_threshold: 0,
+        proposal_count: 0,
+        guardians: Vec::new(),
+        guardian_threshold: 0,
+        recovery_delay: 0,
+        beneficiary: None,
+        inactivity_period: 0,
+        inheritance_mode: InheritanceMode::GrantAdmin,
+        last_heartbeat: clock.unix_timestamp,
+        memory_root: vault_info.key(),
+        migrated_to: None,
+        is_active: legacy.is_active,
+        bump: legacy.bump,
+    };
+    vault.try_serialize(&mut &mut vault_info.try_borrow_mut_data()?[..])?;
+    
+    emit!(VaultLayoutMigrated {
+        vault: vault_info.key(),
+        from_version: 0,
+        to_version: VAULT_LAYOUT_VERSION,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Migrates a memory shard written before `group_key_epoch` to the current layout
+/// 
+/// Like `migrate_vault_layout`, the shard is taken as a raw account and grown
+/// with the owner paying the extra rent. The shard is added to the vault's
+/// usage counters and `shards_created`, which the vault migration reset.
+/// Shards that predate the vault (it was closed and created again) are
+/// migrated as soft-deleted. The vault must be migrated first.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing the vault and the raw shard account
+/// 
+/// # Errors
+/// * `UnauthorizedOwner` - If the signer does not own the vault
+/// * `InvalidShardAccount` - If the account is not a shard PDA of the vault
+/// * `UnsupportedAccountLayout` - If the shard is not in the legacy layout
+pub fn migrate_memory_layout(ctx: Context<MigrateMemoryLayout>) -> Result<()> {
+    let shard_info = ctx.accounts.memory_shard.to_account_info();
+    let clock = Clock::get()?;
+    
+    let legacy: LegacyMemoryShard = read_legacy_account(
+        &shard_info,
+        MemoryShard::DISCRIMINATOR,
+        LegacyMemoryShard::INIT_SPACE,
+        AgentMemoryError::InvalidShardAccount,
+    )?;
+    
+    let vault = &mut ctx.accounts.vault;
+    require_keys_eq!(legacy.vault, vault.memory_root, AgentMemoryError::InvalidShardAccount);
+    let expected_shard = Pubkey::create_program_address(
+        &[b"memory", vault.memory_root.as_ref(), legacy.key.as_bytes(), &[legacy.bump]],
+        &crate::ID,
+    )
+    .map_err(|_| AgentMemoryError::InvalidShardAccount)?;
+    require_keys_eq!(expected_shard, shard_info.key(), AgentMemoryError::InvalidShardAccount);
+    
+    resize_for_migration(
+        &shard_info,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        8 + MemoryShard::INIT_SPACE,
+    )?;
+    
+    let predates_vault = legacy.created_at < vault.created_at;
+    let is_deleted = legacy.is_deleted || predates_vault;
+    if is_deleted {
+        vault.deleted_memory_count = vault
+            .deleted_memory_count
+            .checked_add(1)
+            .ok_or(AgentMemoryError::Overflow)?;
+    } else {
+        vault.memory_count = vault
+            .memory_count
+            .checked_add(1)
+            .ok_or(AgentMemoryError::Overflow)?;
+        vault.total_memory_size = vault
+            .total_memory_size
+            .checked_add(legacy.content_size as u64)
+            .ok_or(AgentMemoryError::Overflow)?;
+    }
+    vault.shards_created = vault
+        .shards_created
+        .checked_add(1)
+        .ok_or(AgentMemoryError::Overflow)?;
+    vault.updated_at = clock.unix_timestamp;
+    
+    let shard = MemoryShard {
+        vault: legacy.vault,
+        key: legacy.key,
+        content_hash: legacy.content_hash,
+        content_size: legacy.content_size,
+        metadata: legacy.metadata,
+        created_at: legacy.created_at,
+        updated_at: legacy.updated_at,
+        version: legacy.version,
+        is_deleted,
+        deleted_at: legacy.deleted_at.or(is_deleted.then_some(clock.unix_timestamp)),
+        previous_version_hash: legacy.previous_version_hash,
+        group_key_epoch: None,
+        bump: legacy.bump,
+    };
+    shard.try_serialize(&mut &mut shard_info.try_borrow_mut_data()?[..])?;
+    
+    emit!(MemoryLayoutMigrated {
+        vault: vault.key(),
+        memory: shard_info.key(),
+        is_deleted: shard.is_deleted,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Migrates an agent profile written before agent key migration to the current layout
+/// 
+/// The profile is grown with the owner paying the extra rent. A legacy profile
+/// must be migrated before the vault can be closed or moved to a new agent key.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing the vault and the raw profile account
+/// 
+/// # Errors
+/// * `UnauthorizedOwner` - If the signer does not own the vault
+/// * `InvalidMigrationAccount` - If the account is not the vault's profile PDA
+/// * `UnsupportedAccountLayout` - If the profile is not in the legacy layout
+pub fn migrate_profile_layout(ctx: Context<MigrateProfileLayout>) -> Result<()> {
+    let profile_info = ctx.accounts.agent_profile.to_account_info();
+    let vault = &ctx.accounts.vault;
+    let clock = Clock::get()?;
+    
+    let legacy: LegacyAgentProfile = read_legacy_account(
+        &profile_info,
+        AgentProfile::DISCRIMINATOR,
+        LegacyAgentProfile::INIT_SPACE,
+        AgentMemoryError::InvalidMigrationAccount,
+    )?;
+    require_keys_eq!(legacy.vault, vault.key(), AgentMemoryError::InvalidMigrationAccount);
+    let expected_profile = Pubkey::create_program_address(
+        &[b"profile", legacy.agent_key.as_ref(), &[legacy.bump]],
+        &crate::ID,
+    )
+    .map_err(|_| AgentMemoryError::InvalidMigrationAccount)?;
+    require_keys_eq!(expected_profile, profile_info.key(), AgentMemoryError::InvalidMigrationAccount);
+    
+    resize_for_migration(
+        &profile_info,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        8 + AgentProfile::INIT_SPACE,
+    )?;
+    
+    let profile = AgentProfile {
+        agent_key: legacy.agent_key,
+        owner: legacy.owner,
+        vault: legacy.vault,
+        name: legacy.name,
+        capabilities: legacy.capabilities,
+        reputation_score: legacy.reputation_score,
+        tasks_completed: legacy.tasks_completed,
+        created_at: legacy.created_at,
+        updated_at: legacy.updated_at,
+        last_task_at: legacy.last_task_at,
+        is_public: legacy.is_public,
+        migrated_to: None,
+        bump: legacy.bump,
+    };
+    profile.try_serialize(&mut &mut profile_info.try_borrow_mut_data()?[..])?;
+    
+    emit!(ProfileLayoutMigrated {
+        vault: vault.key(),
+        profile: profile_info.key(),
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Migrates an access grant written before permission bits to the current layout
+/// 
+/// The grant keeps its level, gets the matching permission bits and a
+/// whole-vault scope, and is grown with the owner paying the extra rent.
+/// Legacy grants were issued under owner epoch 0, so they stop working if
+/// ownership has changed since. Grants that predate the vault (it was closed
+/// and created again) are migrated as revoked. Active grants are added to
+/// `active_grant_count`, which the vault migration started at zero, and every
+/// migrated grant is counted as an open account of the vault.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing the vault and the raw grant account
+/// 
+/// # Errors
+/// * `UnauthorizedOwner` - If the signer does not own the vault
+/// * `InvalidMigrationAccount` - If the account is not a grant PDA of the vault
+/// * `UnsupportedAccountLayout` - If the grant is not in the legacy layout
+pub fn migrate_grant_layout(ctx: Context<MigrateGrantLayout>) -> Result<()> {
+    let grant_info = ctx.accounts.access_grant.to_account_info();
+    let vault = &mut ctx.accounts.vault;
+    let clock = Clock::get()?;
+    
+    let legacy: LegacyAccessGrant = read_legacy_account(
+        &grant_info,
+        AccessGrant::DISCRIMINATOR,
+        LegacyAccessGrant::INIT_SPACE,
+        AgentMemoryError::InvalidMigrationAccount,
+    )?;
+    require_keys_eq!(legacy.vault, vault.key(), AgentMemoryError::InvalidMigrationAccount);
+    let expected_grant = Pubkey::create_program_address(
+        &[b"access", legacy.vault.as_ref(), legacy.grantee.as_ref(), &[legacy.bump]],
+        &crate::ID,
+    )
+    .map_err(|_| AgentMemoryError::InvalidMigrationAccount)?;
+    require_keys_eq!(expected_grant, grant_info.key(), AgentMemoryError::InvalidMigrationAccount);
+    
+    resize_for_migration(
+        &grant_info,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        8 + AccessGrant::INIT_SPACE,
+    )?;
+    
+    let predates_vault = legacy.granted_at < vault.created_at;
+    let is_active = legacy.is_active && !predates_vault;
+    if is_active {
+        vault.active_grant_count = vault
+            .active_grant_count
+            .checked_add(1)
+            .ok_or(AgentMemoryError::Overflow)?;
+    }
+    vault.track_open_account()?;
+    
+    let grant = AccessGrant {
+        vault: legacy.vault,
+        grantee: legacy.grantee,
+        permission_level: legacy.permission_level,
+        permissions: legacy.permission_level.permissions(),
+        granted_at: legacy.granted_at,
+        expires_at: legacy.expires_at,
+        not_before: None,
+        remaining_uses: None,
+        is_active,
+        revoked_at: if legacy.is_active && predates_vault {
+            Some(clock.unix_timestamp)
+        } else {
+            legacy.revoked_at
+        },
+        payer: ctx.accounts.owner.key(),
+        scope: GrantScope::default(),
+        parent_grant: None,
+        parent_granted_at: 0,
+        depth: 0,
+        owner_epoch: 0,
+        bump: legacy.bump,
+    };
+    grant.try_serialize(&mut &mut grant_info.try_borrow_mut_data()?[..])?;
+    
+    emit!(GrantLayoutMigrated {
+        vault: vault.key(),
+        grant: grant_info.key(),
+        grantee: grant.grantee,
+        is_active,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Migrates a sharing group written with an inline member list to the current layout
+/// 
+/// Legacy groups could not gain members, so the empty member list is dropped
+/// and the account shrinks, refunding the surplus rent to the owner. The group
+/// is tagged with owner epoch 0; after an ownership change it stays unusable
+/// until reactivated with `set_group_active(true)`. Groups that predate the
+/// vault (it was closed and created again) are migrated as archived.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing the vault and the raw group account
+/// 
+/// # Errors
+/// * `UnauthorizedOwner` - If the signer does not own the vault
+/// * `InvalidMigrationAccount` - If the account is not a group PDA of the vault
+/// * `UnsupportedAccountLayout` - If the group is not in the legacy layout
+pub fn migrate_group_layout(ctx: Context<MigrateGroupLayout>) -> Result<()> {
+    let group_info = ctx.accounts.sharing_group.to_account_info();
+    let vault = &mut ctx.accounts.vault;
+    let clock = Clock::get()?;
+    
+    let legacy: LegacySharingGroup = read_legacy_account(
+        &group_info,
+        SharingGroup::DISCRIMINATOR,
+        LegacySharingGroup::INIT_SPACE,
+        AgentMemoryError::InvalidMigrationAccount,
+    )?;
+    require_keys_eq!(legacy.vault, vault.key(), AgentMemoryError::InvalidMigrationAccount);
+    require!(legacy.members.is_empty(), AgentMemoryError::UnsupportedAccountLayout);
+    let expected_group = Pubkey::create_program_address(
+        &[b"group", legacy.vault.as_ref(), legacy.name.as_bytes(), &[legacy.bump]],
+        &crate::ID,
+    )
+    .map_err(|_| AgentMemoryError::InvalidMigrationAccount)?;
+    require_keys_eq!(expected_group, group_info.key(), AgentMemoryError::InvalidMigrationAccount);
+    
+    resize_for_migration(
+        &group_info,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        8 + SharingGroup::INIT_SPACE,
+    )?;
+    vault.track_open_account()?;
+    
+    let group = SharingGroup {
+        creator: legacy.creator,
+        pending_creator: None,
+        vault: legacy.vault,
+        seed_name: legacy.name.clone(),
+        name: legacy.name,
+        description: legacy.description,
+        member_count: 0,
+        key_epoch: 0,
+        open_key_epochs: 0,
+        key_rotation_pending: false,
+        created_at: legacy.created_at,
+        updated_at: legacy.updated_at,
+        is_active: legacy.is_active && legacy.created_at >= vault.created_at,
+        owner_epoch: 0,
+        bump: legacy.bump,
+    };
+    group.try_serialize(&mut &mut group_info.try_borrow_mut_data()?[..])?;
+    
+    emit!(GroupLayoutMigrated {
+        vault: vault.key(),
+        group: group_info.key(),
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Sets owner-configured storage quotas on a vault
+/// 
+/// Limits below current usage are allowed; they block further growth until
+/// memories are removed.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing vault account
+/// * `max_memory_count` - Maximum number of memories (0 = unlimited)
+/// * `max_total_bytes` - Maximum total content size (0 = unlimited)
+pub fn set_vault_quota(
+    ctx: Context<SetVaultQuota>,
+    max_memory_count: u32,
+    max_total_bytes: u64,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let clock = Clock::get()?;
+    
+    vault.max_memory_count = max_memory_count;
+    vault.max_total_bytes = max_total_bytes;
+    vault.updated_at = clock.unix_timestamp;
+    vault.last_heartbeat = clock.unix_timestamp;
+    
+    emit!(VaultQuotaUpdated {
+        vault: vault.key(),
+        max_memory_count,
+        max_total_bytes,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Enables or disables importance-based eviction
+/// 
+/// When enabled, a write that would exceed the vault quota soft-deletes the
+/// least important of the candidate shards passed as remaining accounts
+/// instead of failing.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing vault account
+/// * `enabled` - Whether eviction is allowed
+pub fn set_eviction_policy(ctx: Context<SetVaultQuota>, enabled: bool) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let clock = Clock::get()?;
+    
+    vault.eviction_enabled = enabled;
+    vault.updated_at = clock.unix_timestamp;
+    vault.last_heartbeat = clock.unix_timestamp;
+    
+    emit!(EvictionPolicyChanged {
+        vault: vault.key(),
+        enabled,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Tallies a chunk of memory shards towards a vault reconciliation
+/// 
+/// Shards, live or soft-deleted, are passed as remaining accounts, in
+/// strictly ascending address order across all chunks, so a vault of any
+/// size can be reconciled over several transactions without a shard being
+/// counted twice. The first call, or one with `restart`, starts a new tally.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing vault and tally accounts; shards as remaining accounts
+/// * `restart` - Discard any tally in progress and start over with this chunk
+/// 
+/// # Errors
+/// * `ReconciliationStale` - If the vault was written to since the tally started
+/// * `InvalidShardAccount` - If an account is not a live shard of this vault or is out of order
+pub fn reconcile_vault(ctx: Context<ReconcileVault>, restart: bool) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    let tally = &mut ctx.accounts.reconciliation;
+    let clock = Clock::get()?;
+    
+    if restart || tally.vault == Pubkey::default() {
+        tally.vault = vault.key();
+        tally.memory_count = 0;
+        tally.total_memory_size = 0;
+        tally.deleted_memory_count = 0;
+        tally.last_shard = Pubkey::default();
+        tally.vault_updated_at = vault.updated_at;
+        tally.vault_memory_count = vault.memory_count;
+        tally.vault_total_memory_size = vault.total_memory_size;
+        tally.started_at = clock.unix_timestamp;
+        tally.bump = ctx.bumps.reconciliation;
+    }
+    require!(!tally.is_stale(vault), AgentMemoryError::ReconciliationStale);
+    
+    for info in ctx.remaining_accounts {
+        require_keys_eq!(*info.owner, crate::ID, AgentMemoryError::InvalidShardAccount);
+        require!(
+            info.key.to_bytes() > tally.last_shard.to_bytes(),
+            AgentMemoryError::InvalidShardAccount
+        );
+        
+        let shard = MemoryShard::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        require_keys_eq!(shard.vault, vault.memory_root, AgentMemoryError::InvalidShardAccount);
+        
+        if shard.is_deleted {
+            tally.deleted_memory_count = tally
+                .deleted_memory_count
+                .checked_add(1)
+                .ok_or(AgentMemoryError::Overflow)?;
+        } else {
+            tally.memory_count = tally
+                .memory_count
+                .checked_add(1)
+                .ok_or(AgentMemoryError::Overflow)?;
+            tally.total_memory_size = tally
+                .total_memory_size
+                .checked_add(shard.content_size as u64)
+                .ok_or(AgentMemoryError::Overflow)?;
+        }
+        tally.last_shard = *info.key;
+    }
+    
+    Ok(())
+}
+
+/// Replaces vault counters with the reconciled tallies
+/// 
+/// The tally must cover every shard account of the vault, as counted by the
+/// never-decremented `shards_created` and `shards_closed`, so no shard can be
+/// left out. Emits the counters before and after so drift can be audited,
+/// and closes the tally account.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing vault and tally accounts
+/// 
+/// # Errors
+/// * `ReconciliationStale` - If the vault was written to since the tally started
+/// * `ReconciliationIncomplete` - If the tally does not cover every shard
+pub fn finalize_reconcile(ctx: Context<FinalizeReconcile>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let tally = &ctx.accounts.reconciliation;
+    let clock = Clock::get()?;
+    
+    require!(!tally.is_stale(vault), AgentMemoryError::ReconciliationStale);
+    
+    let tallied = tally.memory_count as u64 + tally.deleted_memory_count as u64;
+    require!(
+        tallied == vault.shards_created.saturating_sub(vault.shards_closed),
+        AgentMemoryError::ReconciliationIncomplete
+    );
+    
+    let old_memory_count = vault.memory_count;
+    let old_total_memory_size = vault.total_memory_size;
+    let old_deleted_memory_count = vault.deleted_memory_count;
+    
+    vault.memory_count = tally.memory_count;
+    vault.total_memory_size = tally.total_memory_size;
+    vault.deleted_memory_count = tally.deleted_memory_count;
+    vault.updated_at = clock.unix_timestamp;
+    vault.last_heartbeat = clock.unix_timestamp;
+    
+    emit!(VaultReconciled {
+        vault: vault.key(),
+        old_memory_count,
+        new_memory_count: vault.memory_count,
+        old_total_memory_size,
+        new_total_memory_size: vault.total_memory_size,
+        old_deleted_memory_count,
+        new_deleted_memory_count: vault.deleted_memory_count,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Authorizes a session key to write memories on the owner's behalf
+/// 
+/// Re-authorizing an existing delegate replaces its scope while keeping its
+/// usage for the current day, and re-confirms it for the current owner.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing vault and delegate accounts
+/// * `allowed_memory_types` - Bitmask of writable `MemoryType`s (0 = all)
+/// * `max_bytes_per_day` - Daily write allowance in bytes (0 = unlimited)
+/// * `expires_at` - Optional expiration timestamp
+/// 
+/// # Errors
+/// * `VaultFrozen` - If the vault has been frozen by its owner
+/// * `CannotGrantToOwner` - If the delegate is the vault owner
+/// * `InvalidExpiration` - If expiration is in the past
+pub fn authorize_delegate(
+    ctx: Context<AuthorizeDelegate>,
+    allowed_memory_types: u8,
+    max_bytes_per_day: u64,
+    expires_at: Option<i64>,
+) -> Result<()> {
+    require!(ctx.accounts.vault.is_active, AgentMemoryError::VaultFrozen);
+    
+    let delegate_key = ctx.accounts.delegate_key.key();
+    require!(
+        delegate_key != ctx.accounts.owner.key(),
+        AgentMemoryError::CannotGrantToOwner
+    );
+    
+    let delegate = &mut ctx.accounts.delegate;
+    let clock = Clock::get()?;
+    
+    // Validate expiration if provided
+    if let Some(exp) = expires_at {
+        require!(
+            exp > clock.unix_timestamp,
+            AgentMemoryError::InvalidExpiration
+        );
+        require!(
+            exp <= clock.unix_timestamp + (365 * 24 * 60 * 60),
+            AgentMemoryError::ExpirationTooFar
+        );
+    }
+    
+    if delegate.created_at == 0 {
+        ctx.accounts.vault.track_open_account()?;
+        delegate.vault = ctx.accounts.vault.key();
+        delegate.delegate = delegate_key;
+        delegate.bytes_written_today = 0;
+        delegate.day_started_at = clock.unix_timestamp;
+        delegate.created_at = clock.unix_timestamp;
+        delegate.bump = ctx.bumps.delegate;
+    }
+    delegate.allowed_memory_types = allowed_memory_types;
+    delegate.max_bytes_per_day = max_bytes_per_day;
+    delegate.expires_at = expires_at;
+    delegate.owner_epoch = ctx.accounts.vault.owner_epoch;
+    ctx.accounts.vault.last_heartbeat = clock.unix_timestamp;
+    
+    emit!(DelegateAuthorized {
+        vault: delegate.vault,
+        delegate: delegate_key,
+        allowed_memory_types,
+        max_bytes_per_day,
+        expires_at,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Revokes a delegate and closes its account
+/// 
+/// # Arguments
+/// * `ctx` - Context containing vault and delegate accounts
+pub fn revoke_delegate(ctx: Context<RevokeDelegate>) -> Result<()> {
+    let clock = Clock::get()?;
+    ctx.accounts.vault.release_open_account();
+    ctx.accounts.vault.last_heartbeat = clock.unix_timestamp;
+    
+    emit!(DelegateRevoked {
+        vault: ctx.accounts.vault.key(),
+        delegate: ctx.accounts.delegate.delegate,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
 /// ============================================================================
 /// MEMORY OPERATIONS
 /// ============================================================================
 
+/// Checks that an access grant is live and carries every `required` bit
+fn check_access_grant(grant: &AccessGrant, required: u8, now: i64) -> Result<()> {
+    require!(grant.is_active, AgentMemoryError::AccessNotGranted);
+    if let Some(start) = grant.not_before {
+        require!(now >= start, AgentMemoryError::AccessNotYetValid);
+    }
+    if let Some(exp) = grant.expires_at {
+        require!(now < exp, AgentMemoryError::AccessExpired);
+    }
+    require!(
+        grant.permissions & required == required,
+        AgentMemoryError::AccessNotGranted
+    );
+    Ok(())
+}
+
+/// Checks that an entry was issued under the vault's current owner
+fn check_owner_epoch(vault: &MemoryVault, owner_epoch: u32) -> Result<()> {
+    require!(
+        owner_epoch == vault.owner_epoch,
+        AgentMemoryError::StaleOwnerEpoch
+    );
+    Ok(())
+}
+
+/// Resolves the permission bits of a grant, defaulting to those of `level`
+fn resolve_permissions(level: PermissionLevel, permissions: Option<u8>) -> Result<u8> {
+    let permissions = permissions.unwrap_or(level.permissions());
+    require!(
+        permissions != 0 && permissions & !PERMISSION_ALL == 0,
+        AgentMemoryError::InvalidPermission
+    );
+    Ok(permissions)
+}
+
+/// Checks that a grant scope fits its account space
+/// 
+/// Tag id 0 marks an empty tag slot, so it cannot be required.
+fn validate_grant_scope(scope: &GrantScope) -> Result<()> {
+    require!(
+        scope.key_prefix.len() <= MAX_KEY_LENGTH
+            && scope.required_tags.len() <= MAX_SCOPE_TAGS
+            && !scope.required_tags.contains(&0),
+        AgentMemoryError::InvalidGrantScope
+    );
+    Ok(())
+}
+
+/// Link from a sub-grant or ACL entry to the grant it was issued under
+struct GrantLink {
+    vault: Pubkey,
+    owner_epoch: u32,
+    parent: Option<Pubkey>,
+    parent_granted_at: i64,
+    depth: u8,
+}
+
+impl From<&AccessGrant> for GrantLink {
+    fn from(grant: &AccessGrant) -> Self {
+        GrantLink {
+            vault: grant.vault,
+            owner_epoch: grant.owner_epoch,
+            parent: grant.parent_grant,
+            parent_granted_at: grant.parent_granted_at,
+            depth: grant.depth,
+        }
+    }
+}
+
+impl From<&MemoryAcl> for GrantLink {
+    fn from(acl: &MemoryAcl) -> Self {
+        GrantLink {
+            vault: acl.vault,
+            owner_epoch: acl.owner_epoch,
+            parent: acl.issuer_grant,
+            parent_granted_at: acl.issuer_granted_at,
+            depth: acl.issuer_depth,
+        }
+    }
+}
+
+/// Checks that the ancestors of a sub-grant are live and allow the same action
+/// 
+/// `ancestors` must hold the grant's parent, grandparent, ... in order. Each
+/// must be unrevoked, unexpired, not re-issued since the child was linked to
+/// it, issued under the same vault owner, carry every `required` bit and
+/// cover every shard, so revoking or narrowing a grant takes effect on all of
+/// its descendants. Ancestors must not be usage-limited, since acting through
+/// a descendant spends none of their uses.
+fn check_grant_ancestors(
+    grant: &AccessGrant,
+    ancestors: &[AccountInfo],
+    required: u8,
+    shards: &[(&str, &MemoryMetadata)],
+    now: i64,
+) -> Result<()> {
+    check_grant_chain(grant.into(), ancestors, required, shards, now)
+}
+
+/// Checks the chain of grants above `link`; see `check_grant_ancestors`
+fn check_grant_chain(
+    link: GrantLink,
+    ancestors: &[AccountInfo],
+    required: u8,
+    shards: &[(&str, &MemoryMetadata)],
+    now: i64,
+) -> Result<()> {
+    require!(
+        ancestors.len() >= link.depth as usize,
+        AgentMemoryError::InvalidGrantChain
+    );
+    
+    let mut parent = link.parent;
+    let mut parent_granted_at = link.parent_granted_at;
+    for info in &ancestors[..link.depth as usize] {
+        require!(
+            parent == Some(info.key()) && *info.owner == crate::ID,
+            AgentMemoryError::InvalidGrantChain
+        );
+        let ancestor = AccessGrant::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        require!(
+            ancestor.vault == link.vault && ancestor.granted_at == parent_granted_at,
+            AgentMemoryError::InvalidGrantChain
+        );
+        require!(
+            ancestor.owner_epoch == link.owner_epoch,
+            AgentMemoryError::StaleOwnerEpoch
+        );
+        check_access_grant(&ancestor, required, now)?;
+        require!(
+            ancestor.remaining_uses.is_none(),
+            AgentMemoryError::UsageLimitedGrant
+        );
+        for (key, metadata) in shards {
+            require!(
+                ancestor.scope.allows(key, metadata),
+                AgentMemoryError::GrantScopeViolation
+            );
+        }
+        parent = ancestor.parent_grant;
+        parent_granted_at = ancestor.parent_granted_at;
+    }
+    require!(parent.is_none(), AgentMemoryError::InvalidGrantChain);
+    Ok(())
+}
+
+/// Spends one use of a usage-limited grant, deactivating it at zero
+fn consume_grant_use(vault: &mut MemoryVault, grant: &mut Account<AccessGrant>, now: i64) -> Result<()> {
+    let Some(remaining) = grant.remaining_uses else {
+        return Ok(());
+    };
+    
+    let remaining = remaining.saturating_sub(1);
+    grant.remaining_uses = Some(remaining);
+    if remaining == 0 {
+        grant.is_active = false;
+        vault.active_grant_count = vault.active_grant_count.saturating_sub(1);
+        
+        emit!(AccessExhausted {
+            vault: grant.vault,
+            grantee: grant.grantee,
+            timestamp: now,
+        });
+    }
+    Ok(())
+}
+
+/// Checks the start time and usage limit of a new or updated grant
+fn validate_grant_window(
+    not_before: Option<i64>,
+    expires_at: Option<i64>,
+    max_uses: Option<u32>,
+) -> Result<()> {
+    if let (Some(start), Some(exp)) = (not_before, expires_at) {
+        require!(start < exp, AgentMemoryError::InvalidExpiration);
+    }
+    require!(max_uses != Some(0), AgentMemoryError::InvalidMaxUses);
+    Ok(())
+}
+
+/// Splits grant chains off the front of the remaining accounts
+/// 
+/// Returns the issuer chain of `memory_acl`, then the ancestors of `grant`,
+/// then whatever follows them.
+fn split_grant_ancestors<'a, 'info>(
+    memory_acl: Option<&MemoryAcl>,
+    grant: Option<&AccessGrant>,
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> (&'a [AccountInfo<'info>], &'a [AccountInfo<'info>], &'a [AccountInfo<'info>]) {
+    let acl_depth = memory_acl.map_or(0, |acl| acl.issuer_depth as usize);
+    let (acl_chain, rest) = remaining_accounts.split_at(acl_depth.min(remaining_accounts.len()));
+    let depth = grant.map_or(0, |grant| grant.depth as usize);
+    let (grant_ancestors, rest) = rest.split_at(depth.min(rest.len()));
+    (acl_chain, grant_ancestors, rest)
+}
+
+/// Checks that `authority` may create, change or remove an entry carrying `permissions`
+/// 
+/// The vault owner may manage any entry. Other signers need the `required`
+/// bit (`PERMISSION_GRANT` or `PERMISSION_SHARE`) on their own grant, a live
+/// ancestor chain (see `check_grant_ancestors`) covering `shards`, and may
+/// only hand out bits they hold themselves. Usage-limited grants cannot
+/// manage entries, as doing so spends none of their uses.
+#[allow(clippy::too_many_arguments)]
+fn authorize_grant_manager(
+    vault: &MemoryVault,
+    authority: &Pubkey,
+    authority_grant: Option<&AccessGrant>,
+    ancestors: &[AccountInfo],
+    required: u8,
+    permissions: u8,
+    shards: &[(&str, &MemoryMetadata)],
+    now: i64,
+) -> Result<()> {
+    if *authority == vault.owner {
+        return Ok(());
+    }
+    
+    let grant = authority_grant.ok_or(AgentMemoryError::AccessNotGranted)?;
+    check_owner_epoch(vault, grant.owner_epoch)?;
+    check_access_grant(grant, required, now)?;
+    require!(
+        grant.remaining_uses.is_none(),
+        AgentMemoryError::UsageLimitedGrant
+    );
+    check_grant_ancestors(grant, ancestors, required, shards, now)?;
+    require!(
+        permissions & !grant.permissions == 0,
+        AgentMemoryError::InvalidPermission
+    );
+    Ok(())
+}
+
+/// Checks that a sub-grant is no wider than the grant it is issued under
+/// 
+/// A missing scope, start or expiry is inherited from `parent`. Returns the
+/// resulting scope, start and expiry. Permission bits and usage limits are
+/// checked by `authorize_grant_manager`; the level is only a label, since bit
+/// sets do not form a ladder.
+fn attenuate_sub_grant(
+    parent: &AccessGrant,
+    expires_at: Option<i64>,
+    scope: Option<GrantScope>,
+    not_before: Option<i64>,
+) -> Result<(GrantScope, Option<i64>, Option<i64>)> {
+    require!(parent.depth < MAX_GRANT_DEPTH, AgentMemoryError::GrantDepthExceeded);
+    
+    let scope = scope.unwrap_or_else(|| parent.scope.clone());
+    let not_before = not_before.or(parent.not_before);
+    let expires_at = expires_at.or(parent.expires_at);
+    let within_start = match parent.not_before {
+        Some(min) => not_before.is_some_and(|start| start >= min),
+        None => true,
+    };
+    let within_expiry = match parent.expires_at {
+        Some(max) => expires_at.is_some_and(|exp| exp <= max),
+        None => true,
+    };
+    require!(
+        scope.is_within(&parent.scope) && within_start && within_expiry,
+        AgentMemoryError::SubGrantExceedsParent
+    );
+    Ok((scope, not_before, expires_at))
+}
+
+/// Checks that a non-owner `authority` issued `grant` under its own grant
+fn require_grant_issuer(
+    vault: &MemoryVault,
+    authority: &Pubkey,
+    authority_grant: Option<&Account<AccessGrant>>,
+    grant: &AccessGrant,
+) -> Result<()> {
+    if *authority == vault.owner {
+        return Ok(());
+    }
+    let issuer = authority_grant.map(|issuer| issuer.key());
+    require!(
+        issuer.is_some() && grant.parent_grant == issuer,
+        AgentMemoryError::NotGrantIssuer
+    );
+    Ok(())
+}
+
+/// Checks that a shard-level ACL entry is live and carries every `required` bit
+/// 
+/// Entries shared by a grantee also need the issuer's grant and its
+/// ancestors (`issuer_chain`) to be live and allow the action, so revoking or
+/// narrowing the issuer's grant takes effect on the entries it issued.
+fn check_memory_acl(
+    acl: &MemoryAcl,
+    issuer_chain: &[AccountInfo],
+    required: u8,
+    shards: &[(&str, &MemoryMetadata)],
+    now: i64,
+) -> Result<()> {
+    if let Some(exp) = acl.expires_at {
+        require!(now < exp, AgentMemoryError::AccessExpired);
+    }
+    require!(
+        acl.permissions & required == required,
+        AgentMemoryError::AccessNotGranted
+    );
+    check_grant_chain(acl.into(), issuer_chain, required, shards, now)
+}
+
+/// Checks that `membership` is a record of an active group holding every `required` bit
+/// 
+/// Fails while the group key awaits rotation after a member was removed.
+fn check_group_access(
+    group: &Account<SharingGroup>,
+    membership: &GroupMember,
+    required: u8,
+) -> Result<()> {
+    require!(group.is_active, AgentMemoryError::GroupInactive);
+    require!(!group.key_rotation_pending, AgentMemoryError::StaleGroupKey);
+    require_keys_eq!(membership.group, group.key(), AgentMemoryError::MemberNotFound);
+    require!(
+        membership.permissions & required == required,
+        AgentMemoryError::AccessNotGranted
+    );
+    Ok(())
+}
+
+/// Checks that `authority` may write the given `(key, metadata)` shards to `vault`
+/// 
+/// The vault owner is always allowed. A delegate must present its
+/// `SessionDelegate`, which is checked for expiry and memory type scope and
+/// charged `bytes` against its daily allowance. Any other signer must present
+/// a live `MemoryAcl` entry for the shard (with its issuer's grant chain), or
+/// failing that an active,
+/// unexpired `AccessGrant` whose scope covers every shard, or failing that
+/// membership of an active `SharingGroup` of the vault. Each must carry the
+/// `required` permission bit, as must every ancestor of a sub-grant. Entries
+/// issued under a previous vault owner are rejected. Writes
+/// authorized by a usage-limited grant spend one of its uses; ancestors and
+/// ACL issuers may not be usage-limited.
+/// 
+/// Returns the scope of shards the signer may evict (`None` = none). Only
+/// signers that may delete can evict: the owner, delegates within their memory
+/// types, and grantees (with every ancestor) or group members holding
+/// `PERMISSION_DELETE`. ACL entries never allow eviction.
+#[allow(clippy::too_many_arguments)]
+fn authorize_memory_write(
+    vault: &mut MemoryVault,
+    authority: &Pubkey,
+    delegate: Option<&mut SessionDelegate>,
+    memory_acl: Option<(&MemoryAcl, &[AccountInfo])>,
+    access_grant: Option<&mut Account<AccessGrant>>,
+    grant_ancestors: &[AccountInfo],
+    group_member: Option<(&Account<SharingGroup>, &GroupMember)>,
+    required: u8,
+    shards: &[(&str, &MemoryMetadata)],
+    bytes: u64,
+    now: i64,
+) -> Result<Option<GrantScope>> {
+    if *authority == vault.owner {
+        return Ok(Some(GrantScope::default()));
+    }
+    
+    let Some(delegate) = delegate else {
+        // Shard-level entries take precedence over the vault-level grant,
+        // which takes precedence over group membership
+        let mut result: Result<Option<GrantScope>> = err!(AgentMemoryError::AccessNotGranted);
+        if let Some((acl, issuer_chain)) = memory_acl {
+            result = check_owner_epoch(vault, acl.owner_epoch)
+                .and_then(|()| check_memory_acl(acl, issuer_chain, required, shards, now))
+                .map(|()| None);
+        }
+        if let (Err(_), Some(grant)) = (&result, access_grant) {
+            result = check_owner_epoch(vault, grant.owner_epoch).and_then(|()| {
+                check_access_grant(grant, required, now)?;
+                for (key, metadata) in shards {
+                    require!(
+                        grant.scope.allows(key, metadata),
+                        AgentMemoryError::GrantScopeViolation
+                    );
+                }
+                check_grant_ancestors(grant, grant_ancestors, required, shards, now)?;
+                consume_grant_use(vault, grant, now)?;
+                let can_evict = grant.permissions & PERMISSION_DELETE != 0
+                    && check_grant_ancestors(grant, grant_ancestors, PERMISSION_DELETE, &[], now)
+                        .is_ok();
+                Ok(can_evict.then(|| grant.scope.clone()))
+            });
+        }
+        if let (Err(_), Some((group, membership))) = (&result, group_member) {
+            result = check_owner_epoch(vault, group.owner_epoch)
+                .and_then(|()| check_group_access(group, membership, required))
+                .map(|()| {
+                    (membership.permissions & PERMISSION_DELETE != 0).then(GrantScope::default)
+                });
+        }
+        return result;
+    };
+    
+    check_owner_epoch(vault, delegate.owner_epoch)?;
+    if let Some(exp) = delegate.expires_at {
+        require!(now < exp, AgentMemoryError::DelegateExpired);
+    }
+    
+    let scope = GrantScope {
+        memory_types: delegate.allowed_memory_types,
+        ..GrantScope::default()
+    };
+    for (key, metadata) in shards {
+        require!(
+            scope.allows(key, metadata),
+            AgentMemoryError::DelegateScopeViolation
+        );
+    }
+    
+    // Start a new allowance window once a day has passed
+    if now - delegate.day_started_at >= SECONDS_PER_DAY {
+        delegate.day_started_at = now - now.rem_euclid(SECONDS_PER_DAY);
+        delegate.bytes_written_today = 0;
+    }
+    
+    let written = delegate
+        .bytes_written_today
+        .checked_add(bytes)
+        .ok_or(AgentMemoryError::Overflow)?;
+    if delegate.max_bytes_per_day != 0 {
+        require!(
+            written <= delegate.max_bytes_per_day,
+            AgentMemoryError::DelegateQuotaExceeded
+        );
+    }
+    delegate.bytes_written_today = written;
+    
+    Ok(Some(scope))
+}
+
+/// Rejects vault growth beyond the owner-set quota
+/// 
+/// Called after the vault counters have been updated. A vault that is
+/// already over quota (e.g. after the owner lowered it) still accepts
+/// writes that don't increase usage. Emits `QuotaThresholdReached` when
+/// usage of either limit crosses one of `QUOTA_THRESHOLDS_PERCENT` relative
+/// to `prev_count` / `prev_size`.
+fn check_vault_quota(
+    vault: &Account<MemoryVault>,
+    prev_count: u32,
+    prev_size: u64,
+    now: i64,
+) -> Result<()> {
+    require!(
+        !exceeds_quota(prev_count as u64, vault.memory_count as u64, vault.max_memory_count as u64)
+            && !exceeds_quota(prev_size, vault.total_memory_size, vault.max_total_bytes),
+        AgentMemoryError::QuotaExceeded
+    );
+    
+    let count_limit = vault.max_memory_count as u64;
+    let crossed = QUOTA_THRESHOLDS_PERCENT.iter().rev().find(|&&threshold| {
+        crosses_threshold(prev_count as u64, vault.memory_count as u64, count_limit, threshold)
+            || crosses_threshold(prev_size, vault.total_memory_size, vault.max_total_bytes, threshold)
+    });
+    
+    if let Some(&threshold_percent) = crossed {
+        emit!(QuotaThresholdReached {
+            vault: vault.key(),
+            threshold_percent,
+            memory_count: vault.memory_count,
+            max_memory_count: vault.max_memory_count,
+            total_memory_size: vault.total_memory_size,
+            max_total_bytes: vault.max_total_bytes,
+            timestamp: now,
+        });
+    }
+    
+    Ok(())
+}
+
+/// Whether a write grew usage from `before` to `after` past `limit` (0 = unlimited)
+fn exceeds_quota(before: u64, after: u64, limit: u64) -> bool {
+    limit != 0 && after > limit && after > before
+}
+
+/// Whether usage moved from below to at-or-above `threshold` percent of `limit`
+fn crosses_threshold(before: u64, after: u64, limit: u64, threshold: u8) -> bool {
+    if limit == 0 {
+        return false;
+    }
+    let mark = (limit as u128) * (threshold as u128);
+    (before as u128) * 100 < mark && (after as u128) * 100 >= mark
+}
+
+/// Makes room for a write by soft-deleting caller-supplied candidate shards
+/// 
+/// Only runs when the vault has eviction enabled and is over quota.
+/// Candidates are evicted lowest `importance` first, ties broken by oldest
+/// `updated_at`, until the vault fits. Candidates that are already deleted,
+/// `protected`, or outside the writer's `scope` are skipped.
+fn evict_for_quota(
+    vault: &mut Account<MemoryVault>,
+    candidates: &[AccountInfo],
+    protected: Option<Pubkey>,
+    scope: Option<&GrantScope>,
+    now: i64,
+) -> Result<()> {
+    if !vault.eviction_enabled || !vault.exceeds_quota() {
+        return Ok(());
+    }
+    
+    let mut shards: Vec<(&AccountInfo, MemoryShard)> = Vec::with_capacity(candidates.len());
+    for info in candidates {
+        require_keys_eq!(*info.owner, crate::ID, AgentMemoryError::InvalidEvictionCandidate);
+        require!(info.is_writable, AgentMemoryError::InvalidEvictionCandidate);
+        
+        let shard = MemoryShard::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        require_keys_eq!(shard.vault, vault.memory_root, AgentMemoryError::InvalidEvictionCandidate);
+        
+        let in_scope = scope.is_some_and(|scope| scope.allows(&shard.key, &shard.metadata));
+        let is_duplicate = shards.iter().any(|(seen, _)| seen.key == info.key);
+        if shard.is_deleted || protected == Some(info.key()) || !in_scope || is_duplicate {
+            continue;
+        }
+        shards.push((info, shard));
+    }
+    
+    shards.sort_by(|(_, a), (_, b)| {
+        a.metadata
+            .importance
+            .cmp(&b.metadata.importance)
+            .then(a.updated_at.cmp(&b.updated_at))
+    });
+    
+    for (info, mut shard) in shards {
+        if !vault.exceeds_quota() {
+            break;
+        }
+        
+        shard.is_deleted = true;
+        shard.deleted_at = Some(now);
+        shard.updated_at = now;
+        shard.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        
+        vault.memory_count = vault.memory_count.saturating_sub(1);
+        vault.total_memory_size = vault
+            .total_memory_size
+            .saturating_sub(shard.content_size as u64);
+        vault.deleted_memory_count = vault
+            .deleted_memory_count
+            .checked_add(1)
+            .ok_or(AgentMemoryError::Overflow)?;
+        
+        emit!(MemoryEvicted {
+            vault: vault.key(),
+            memory: info.key(),
+            key: shard.key,
+            importance: shard.metadata.importance,
+            content_size: shard.content_size,
+            timestamp: now,
+        });
+    }
+    
+    Ok(())
+}
+
 /// Creates a new encrypted memory shard
 /// 
+/// If the vault has eviction enabled, candidate shards for eviction may be
+/// passed as remaining accounts, after the ancestors of a sub-grant.
+/// 
 /// # Arguments
 /// * `ctx` - Context containing vault and memory shard accounts
 /// * `key` - Memory key identifier
@@ -81,6 +1686,16 @@ pub fn initialize_vault(
 /// 
 /// # Errors
 /// * `ProgramPaused` - If protocol is currently paused
+/// * `VaultFrozen` - If the vault has been frozen by its owner
+/// * `QuotaExceeded` - If the write exceeds the vault quota
+/// * `AccessNotGranted` - If signer is not the owner, a delegate, a permitted grantee or group member
+/// * `AccessExpired` - If the signer's access grant has expired
+/// * `AccessNotYetValid` - If the signer's access grant has not started yet
+/// * `InvalidGrantChain` - If a sub-grant's ancestors are missing or re-issued
+/// * `UsageLimitedGrant` - If an ancestor of a sub-grant is usage-limited
+/// * `DelegateScopeViolation` - If the memory type is outside the delegate scope
+/// * `InvalidGroupKey` - If the group key does not belong to the given sharing group, or a member write omits the current key
+/// * `StaleGroupKey` - If the group key is not the group's current, unrotated key
 /// * `KeyTooLong` - If key exceeds max length
 /// * `ContentTooLarge` - If content exceeds max size
 /// * `InvalidContentSize` - If content size is zero
@@ -97,6 +1712,9 @@ pub fn create_memory(
         AgentMemoryError::ProtocolPaused
     );
     
+    // Check vault is not frozen
+    require!(ctx.accounts.vault.is_active, AgentMemoryError::VaultFrozen);
+    
     // Validate key
     require!(!key.is_empty(), AgentMemoryError::EmptyKey);
     require!(
@@ -120,9 +1738,38 @@ pub fn create_memory(
         AgentMemoryError::InvalidImportance
     );
     
+    let clock = Clock::get()?;
+    let (_, grant_ancestors, eviction_candidates) =
+        split_grant_ancestors(None, ctx.accounts.access_grant.as_deref(), ctx.remaining_accounts);
+    
+    // Check signer is the owner, a scoped delegate, a permitted grantee or group member
+    let write_scope = authorize_memory_write(
+        &mut ctx.accounts.vault,
+        &ctx.accounts.authority.key(),
+        ctx.accounts.delegate.as_deref_mut(),
+        None,
+        ctx.accounts.access_grant.as_mut(),
+        grant_ancestors,
+        ctx.accounts
+            .sharing_group
+            .as_ref()
+            .zip(ctx.accounts.group_member.as_deref()),
+        PERMISSION_CREATE,
+        &[(key.as_str(), &metadata)],
+        content_size as u64,
+        clock.unix_timestamp,
+    )?;
+    
+    let group_key_epoch = resolve_group_key(
+        ctx.accounts.sharing_group.as_ref(),
+        ctx.accounts.group_member.as_deref(),
+        ctx.accounts.group_key_epoch.as_ref(),
+    )?;
+    
     let vault = &mut ctx.accounts.vault;
     let memory_shard = &mut ctx.accounts.memory_shard;
-    let clock = Clock::get()?;
+    let prev_count = vault.memory_count;
+    let prev_size = vault.total_memory_size;
     
     // Update vault stats with checked arithmetic
     vault.memory_count = vault
@@ -133,10 +1780,24 @@ pub fn create_memory(
         .total_memory_size
         .checked_add(content_size as u64)
         .ok_or(AgentMemoryError::Overflow)?;
+    vault.shards_created = vault
+        .shards_created
+        .checked_add(1)
+        .ok_or(AgentMemoryError::Overflow)?;
     vault.updated_at = clock.unix_timestamp;
+    vault.record_heartbeat(&ctx.accounts.authority.key(), clock.unix_timestamp);
+    
+    evict_for_quota(
+        vault,
+        eviction_candidates,
+        Some(memory_shard.key()),
+        write_scope.as_ref(),
+        clock.unix_timestamp,
+    )?;
+    check_vault_quota(vault, prev_count, prev_size, clock.unix_timestamp)?;
     
     // Initialize memory shard
-    memory_shard.vault = vault.key();
+    memory_shard.vault = vault.memory_root;
     memory_shard.key = key.clone();
     memory_shard.content_hash = content_hash;
     memory_shard.content_size = content_size;
@@ -147,6 +1808,7 @@ pub fn create_memory(
     memory_shard.is_deleted = false;
     memory_shard.deleted_at = None;
     memory_shard.previous_version_hash = None;
+    memory_shard.group_key_epoch = group_key_epoch;
     memory_shard.bump = ctx.bumps.memory_shard;
     
     emit!(MemoryCreated {
@@ -163,6 +1825,10 @@ pub fn create_memory(
 
 /// Updates an existing memory shard
 /// 
+/// If the vault has eviction enabled, candidate shards for eviction may be
+/// passed as remaining accounts, after the issuer chain of a shared ACL entry
+/// and the ancestors of a sub-grant.
+/// 
 /// # Arguments
 /// * `ctx` - Context containing vault and memory shard accounts
 /// * `content_hash` - New content hash
@@ -171,6 +1837,16 @@ pub fn create_memory(
 /// 
 /// # Errors
 /// * `ProgramPaused` - If protocol is currently paused
+/// * `VaultFrozen` - If the vault has been frozen by its owner
+/// * `QuotaExceeded` - If the write exceeds the vault quota
+/// * `AccessNotGranted` - If signer is not the owner, a delegate, a permitted grantee or group member
+/// * `AccessExpired` - If the signer's access grant has expired
+/// * `AccessNotYetValid` - If the signer's access grant has not started yet
+/// * `InvalidGrantChain` - If a sub-grant's ancestors are missing or re-issued
+/// * `UsageLimitedGrant` - If an ancestor of a sub-grant is usage-limited
+/// * `DelegateScopeViolation` - If the memory type is outside the delegate scope
+/// * `InvalidGroupKey` - If the group key does not belong to the given sharing group, or a member write omits the current key
+/// * `StaleGroupKey` - If the group key is not the group's current, unrotated key
 /// * `MemoryAlreadyDeleted` - If memory is already deleted
 /// * `ContentTooLarge` - If content exceeds max size
 pub fn update_memory(
@@ -185,6 +1861,9 @@ pub fn update_memory(
         AgentMemoryError::ProtocolPaused
     );
     
+    // Check vault is not frozen
+    require!(ctx.accounts.vault.is_active, AgentMemoryError::VaultFrozen);
+    
     // Validate content size
     require!(
         content_size <= MAX_CONTENT_SIZE,
@@ -197,12 +1876,47 @@ pub fn update_memory(
         AgentMemoryError::InvalidImportance
     );
     
+    let clock = Clock::get()?;
+    let (acl_chain, grant_ancestors, eviction_candidates) = split_grant_ancestors(
+        ctx.accounts.memory_acl.as_deref(),
+        ctx.accounts.access_grant.as_deref(),
+        ctx.remaining_accounts,
+    );
+    
+    // Check signer is the owner, a scoped delegate, a permitted grantee or group member
+    let write_scope = authorize_memory_write(
+        &mut ctx.accounts.vault,
+        &ctx.accounts.authority.key(),
+        ctx.accounts.delegate.as_deref_mut(),
+        ctx.accounts.memory_acl.as_deref().map(|acl| (acl, acl_chain)),
+        ctx.accounts.access_grant.as_mut(),
+        grant_ancestors,
+        ctx.accounts
+            .sharing_group
+            .as_ref()
+            .zip(ctx.accounts.group_member.as_deref()),
+        PERMISSION_UPDATE,
+        &[
+            (ctx.accounts.memory_shard.key.as_str(), &ctx.accounts.memory_shard.metadata),
+            (ctx.accounts.memory_shard.key.as_str(), &metadata),
+        ],
+        content_size as u64,
+        clock.unix_timestamp,
+    )?;
+    
+    let group_key_epoch = resolve_group_key(
+        ctx.accounts.sharing_group.as_ref(),
+        ctx.accounts.group_member.as_deref(),
+        ctx.accounts.group_key_epoch.as_ref(),
+    )?;
+    
     let vault = &mut ctx.accounts.vault;
     let memory_shard = &mut ctx.accounts.memory_shard;
-    let clock = Clock::get()?;
     
     let old_version = memory_shard.version;
     let old_size = memory_shard.content_size;
+    let prev_count = vault.memory_count;
+    let prev_size = vault.total_memory_size;
     
     // Update vault size with checked arithmetic
     if content_size > old_size {
@@ -218,6 +1932,15 @@ pub fn update_memory(
             .saturating_sub(diff);
     }
     
+    evict_for_quota(
+        vault,
+        eviction_candidates,
+        Some(memory_shard.key()),
+        write_scope.as_ref(),
+        clock.unix_timestamp,
+    )?;
+    check_vault_quota(vault, prev_count, prev_size, clock.unix_timestamp)?;
+    
     // Store previous version hash
     memory_shard.previous_version_hash = Some(memory_shard.content_hash);
     
@@ -225,6 +1948,7 @@ pub fn update_memory(
     memory_shard.content_hash = content_hash;
     memory_shard.content_size = content_size;
     memory_shard.metadata = metadata;
+    memory_shard.group_key_epoch = group_key_epoch;
     memory_shard.updated_at = clock.unix_timestamp;
     memory_shard.version = memory_shard
         .version
@@ -232,6 +1956,7 @@ pub fn update_memory(
         .ok_or(AgentMemoryError::Overflow)?;
     
     vault.updated_at = clock.unix_timestamp;
+    vault.record_heartbeat(&ctx.accounts.authority.key(), clock.unix_timestamp);
     
     emit!(MemoryUpdated {
         vault: vault.key(),
@@ -248,11 +1973,20 @@ pub fn update_memory(
 
 /// Soft deletes a memory shard
 /// 
+/// The ancestors of a sub-grant are passed as remaining accounts.
+/// 
 /// # Arguments
 /// * `ctx` - Context containing vault and memory shard accounts
 /// 
 /// # Errors
 /// * `ProgramPaused` - If protocol is currently paused
+/// * `VaultFrozen` - If the vault has been frozen by its owner
+/// * `AccessNotGranted` - If signer is not the owner, a delegate, a permitted grantee or group member
+/// * `AccessExpired` - If the signer's access grant has expired
+/// * `AccessNotYetValid` - If the signer's access grant has not started yet
+/// * `InvalidGrantChain` - If a sub-grant's ancestors are missing or re-issued
+/// * `UsageLimitedGrant` - If an ancestor of a sub-grant is usage-limited
+/// * `DelegateScopeViolation` - If the memory type is outside the delegate scope
 /// * `MemoryAlreadyDeleted` - If memory is already deleted
 pub fn delete_memory(ctx: Context<DeleteMemory>) -> Result<()> {
     // Check program is not paused
@@ -261,15 +1995,57 @@ pub fn delete_memory(ctx: Context<DeleteMemory>) -> Result<()> {
         AgentMemoryError::ProtocolPaused
     );
     
-    let memory_shard = &mut ctx.accounts.memory_shard;
+    // Check vault is not frozen
+    require!(ctx.accounts.vault.is_active, AgentMemoryError::VaultFrozen);
+    
     let clock = Clock::get()?;
+    let (acl_chain, grant_ancestors, _) = split_grant_ancestors(
+        ctx.accounts.memory_acl.as_deref(),
+        ctx.accounts.access_grant.as_deref(),
+        ctx.remaining_accounts,
+    );
+    
+    // Check signer is the owner, a scoped delegate, a permitted grantee or group member
+    authorize_memory_write(
+        &mut ctx.accounts.vault,
+        &ctx.accounts.authority.key(),
+        ctx.accounts.delegate.as_deref_mut(),
+        ctx.accounts.memory_acl.as_deref().map(|acl| (acl, acl_chain)),
+        ctx.accounts.access_grant.as_mut(),
+        grant_ancestors,
+        ctx.accounts
+            .sharing_group
+            .as_ref()
+            .zip(ctx.accounts.group_member.as_deref()),
+        PERMISSION_DELETE,
+        &[(ctx.accounts.memory_shard.key.as_str(), &ctx.accounts.memory_shard.metadata)],
+        0,
+        clock.unix_timestamp,
+    )?;
+    
+    let vault = &mut ctx.accounts.vault;
+    let memory_shard = &mut ctx.accounts.memory_shard;
+    
+    // Soft-deleted memories no longer count toward vault usage, but still hold rent
+    vault.memory_count = vault
+        .memory_count
+        .saturating_sub(1);
+    vault.total_memory_size = vault
+        .total_memory_size
+        .saturating_sub(memory_shard.content_size as u64);
+    vault.deleted_memory_count = vault
+        .deleted_memory_count
+        .checked_add(1)
+        .ok_or(AgentMemoryError::Overflow)?;
+    vault.updated_at = clock.unix_timestamp;
+    vault.record_heartbeat(&ctx.accounts.authority.key(), clock.unix_timestamp);
     
     memory_shard.is_deleted = true;
     memory_shard.deleted_at = Some(clock.unix_timestamp);
     memory_shard.updated_at = clock.unix_timestamp;
     
     emit!(MemoryDeleted {
-        vault: ctx.accounts.vault.key(),
+        vault: vault.key(),
         memory: memory_shard.key(),
         key: memory_shard.key.clone(),
         timestamp: clock.unix_timestamp,
@@ -284,20 +2060,37 @@ pub fn delete_memory(ctx: Context<DeleteMemory>) -> Result<()> {
 /// * `ctx` - Context containing vault and memory shard accounts
 /// 
 /// # Errors
+/// * `VaultFrozen` - If the vault has been frozen by its owner
 /// * `MemoryNotDeleted` - If memory is not soft-deleted first
+/// * `MultisigApprovalRequired` - If the vault has approvers and no approved proposal is given
 pub fn permanent_delete_memory(ctx: Context<PermanentDeleteMemory>) -> Result<()> {
-    let vault = &mut ctx.accounts.vault;
-    let memory_shard = &ctx.accounts.memory_shard;
+    // Check vault is not frozen
+    require!(ctx.accounts.vault.is_active, AgentMemoryError::VaultFrozen);
+    
     let clock = Clock::get()?;
     
-    // Update vault stats
-    vault.memory_count = vault
-        .memory_count
-        .saturating_sub(1);
-    vault.total_memory_size = vault
-        .total_memory_size
-        .saturating_sub(memory_shard.content_size as u64);
+    // Permanent deletion needs multisig approval when configured
+    require_approval(
+        &ctx.accounts.vault,
+        ctx.accounts.proposal.as_mut(),
+        &ProposalAction::PermanentDeleteMemory {
+            memory: ctx.accounts.memory_shard.key(),
+        },
+        ctx.accounts.owner.key(),
+        clock.unix_timestamp,
+    )?;
+    
+    let vault = &mut ctx.accounts.vault;
+    let memory_shard = &ctx.accounts.memory_shard;
+    
+    // Usage was already released when the memory was soft-deleted
+    vault.deleted_memory_count = vault.deleted_memory_count.saturating_sub(1);
+    vault.shards_closed = vault
+        .shards_closed
+        .checked_add(1)
+        .ok_or(AgentMemoryError::Overflow)?;
     vault.updated_at = clock.unix_timestamp;
+    vault.last_heartbeat = clock.unix_timestamp;
     
     emit!(MemoryPermanentlyDeleted {
         vault: vault.key(),
@@ -311,11 +2104,21 @@ pub fn permanent_delete_memory(ctx: Context<PermanentDeleteMemory>) -> Result<()
 
 /// Restores a soft-deleted memory shard
 /// 
+/// The ancestors of a sub-grant are passed as remaining accounts.
+/// 
 /// # Arguments
 /// * `ctx` - Context containing vault and memory shard accounts
 /// 
 /// # Errors
 /// * `ProgramPaused` - If protocol is currently paused
+/// * `VaultFrozen` - If the vault has been frozen by its owner
+/// * `AccessNotGranted` - If signer is not the owner, a delegate, a permitted grantee or group member
+/// * `AccessExpired` - If the signer's access grant has expired
+/// * `AccessNotYetValid` - If the signer's access grant has not started yet
+/// * `InvalidGrantChain` - If a sub-grant's ancestors are missing or re-issued
+/// * `UsageLimitedGrant` - If an ancestor of a sub-grant is usage-limited
+/// * `MemoryNotDeleted` - If memory is not soft-deleted
+/// * `QuotaExceeded` - If restoring exceeds the vault quota
 pub fn restore_memory(ctx: Context<RestoreMemory>) -> Result<()> {
     // Check program is not paused
     require!(
@@ -323,15 +2126,60 @@ pub fn restore_memory(ctx: Context<RestoreMemory>) -> Result<()> {
         AgentMemoryError::ProtocolPaused
     );
     
-    let memory_shard = &mut ctx.accounts.memory_shard;
+    // Check vault is not frozen
+    require!(ctx.accounts.vault.is_active, AgentMemoryError::VaultFrozen);
+    
     let clock = Clock::get()?;
+    let (acl_chain, grant_ancestors, _) = split_grant_ancestors(
+        ctx.accounts.memory_acl.as_deref(),
+        ctx.accounts.access_grant.as_deref(),
+        ctx.remaining_accounts,
+    );
+    
+    // Check signer is the owner, a scoped delegate, a permitted grantee or group member
+    authorize_memory_write(
+        &mut ctx.accounts.vault,
+        &ctx.accounts.authority.key(),
+        ctx.accounts.delegate.as_deref_mut(),
+        ctx.accounts.memory_acl.as_deref().map(|acl| (acl, acl_chain)),
+        ctx.accounts.access_grant.as_mut(),
+        grant_ancestors,
+        ctx.accounts
+            .sharing_group
+            .as_ref()
+            .zip(ctx.accounts.group_member.as_deref()),
+        PERMISSION_RESTORE,
+        &[(ctx.accounts.memory_shard.key.as_str(), &ctx.accounts.memory_shard.metadata)],
+        ctx.accounts.memory_shard.content_size as u64,
+        clock.unix_timestamp,
+    )?;
+    
+    let vault = &mut ctx.accounts.vault;
+    let memory_shard = &mut ctx.accounts.memory_shard;
+    let prev_count = vault.memory_count;
+    let prev_size = vault.total_memory_size;
+    
+    // Restored memories count toward vault usage again
+    vault.memory_count = vault
+        .memory_count
+        .checked_add(1)
+        .ok_or(AgentMemoryError::Overflow)?;
+    vault.total_memory_size = vault
+        .total_memory_size
+        .checked_add(memory_shard.content_size as u64)
+        .ok_or(AgentMemoryError::Overflow)?;
+    vault.deleted_memory_count = vault.deleted_memory_count.saturating_sub(1);
+    vault.updated_at = clock.unix_timestamp;
+    vault.record_heartbeat(&ctx.accounts.authority.key(), clock.unix_timestamp);
+    
+    check_vault_quota(vault, prev_count, prev_size, clock.unix_timestamp)?;
     
     memory_shard.is_deleted = false;
     memory_shard.deleted_at = None;
     memory_shard.updated_at = clock.unix_timestamp;
     
     emit!(MemoryRestored {
-        vault: ctx.accounts.vault.key(),
+        vault: vault.key(),
         memory: memory_shard.key(),
         key: memory_shard.key.clone(),
         timestamp: clock.unix_timestamp,
@@ -348,6 +2196,7 @@ pub fn restore_memory(ctx: Context<RestoreMemory>) -> Result<()> {
 /// 
 /// # Errors
 /// * `ProgramPaused` - If protocol is currently paused
+/// * `VaultFrozen` - If the vault has been frozen by its owner
 /// * `InvalidRollbackVersion` - If target version is invalid
 pub fn rollback_memory(
     ctx: Context<RollbackMemory>,
@@ -359,6 +2208,9 @@ pub fn rollback_memory(
         AgentMemoryError::ProtocolPaused
     );
     
+    // Check vault is not frozen
+    require!(ctx.accounts.vault.is_active, AgentMemoryError::VaultFrozen);
+    
     let memory_shard = &mut ctx.accounts.memory_shard;
     let clock = Clock::get()?;
     
@@ -374,6 +2226,7 @@ pub fn rollback_memory(
         .checked_add(1)
         .ok_or(AgentMemoryError::Overflow)?;
     memory_shard.updated_at = clock.unix_timestamp;
+    ctx.accounts.vault.last_heartbeat = clock.unix_timestamp;
     
     emit!(MemoryRolledBack {
         vault: ctx.accounts.vault.key(),
@@ -394,12 +2247,17 @@ pub fn rollback_memory(
 
 /// Creates multiple memory shards in a single transaction
 /// 
+/// If the vault has eviction enabled, candidate shards for eviction may be
+/// passed as remaining accounts.
+/// 
 /// # Arguments
 /// * `ctx` - Context containing vault account
 /// * `memories` - Vector of memory inputs
 /// 
 /// # Errors
 /// * `ProgramPaused` - If protocol is currently paused
+/// * `VaultFrozen` - If the vault has been frozen by its owner
+/// * `QuotaExceeded` - If the write exceeds the vault quota
 /// * `EmptyBatch` - If memories vector is empty
 /// * `BatchTooLarge` - If batch exceeds max size
 /// * `ArithmeticOverflow` - If memory count overflow
@@ -413,6 +2271,9 @@ pub fn batch_create_memories(
         AgentMemoryError::ProtocolPaused
     );
     
+    // Check vault is not frozen
+    require!(ctx.accounts.vault.is_active, AgentMemoryError::VaultFrozen);
+    
     require!(!memories.is_empty(), AgentMemoryError::EmptyBatch);
     require!(
         memories.len() <= MAX_BATCH_SIZE,
@@ -441,6 +2302,9 @@ pub fn batch_create_memories(
             .ok_or(AgentMemoryError::Overflow)?;
     }
     
+    let prev_count = vault.memory_count;
+    let prev_size = vault.total_memory_size;
+    
     // Update vault stats
     vault.memory_count = new_count as u32;
     vault.total_memory_size = vault
@@ -448,6 +2312,16 @@ pub fn batch_create_memories(
         .checked_add(total_size)
         .ok_or(AgentMemoryError::Overflow)?;
     vault.updated_at = clock.unix_timestamp;
+    vault.last_heartbeat = clock.unix_timestamp;
+    
+    evict_for_quota(
+        vault,
+        ctx.remaining_accounts,
+        None,
+        Some(&GrantScope::default()),
+        clock.unix_timestamp,
+    )?;
+    check_vault_quota(vault, prev_count, prev_size, clock.unix_timestamp)?;
     
     emit!(BatchMemoryCreated {
         vault: vault.key(),
@@ -469,6 +2343,7 @@ pub fn batch_create_memories(
 /// 
 /// # Errors
 /// * `ProgramPaused` - If protocol is currently paused
+/// * `VaultFrozen` - If the vault has been frozen by its owner
 pub fn batch_delete_memories(
     ctx: Context<BatchDeleteMemories>,
     keys: Vec<String>,
@@ -479,6 +2354,9 @@ pub fn batch_delete_memories(
         AgentMemoryError::ProtocolPaused
     );
     
+    // Check vault is not frozen
+    require!(ctx.accounts.vault.is_active, AgentMemoryError::VaultFrozen);
+    
     require!(!keys.is_empty(), AgentMemoryError::EmptyBatch);
     require!(
         keys.len() <= MAX_BATCH_SIZE,
@@ -493,6 +2371,7 @@ pub fn batch_delete_memories(
         .memory_count
         .saturating_sub(keys.len() as u32);
     vault.updated_at = clock.unix_timestamp;
+    vault.last_heartbeat = clock.unix_timestamp;
     
     emit!(BatchMemoryDeleted {
         vault: vault.key(),
@@ -510,19 +2389,42 @@ pub fn batch_delete_memories(
 
 /// Grants access to a vault
 /// 
+/// A grant issued by a grantee holding `PERMISSION_GRANT` becomes a sub-grant
+/// linked to the issuer's grant. Its permission bits, scope and expiry may
+/// not exceed the parent's; a missing scope or expiry is inherited. The issuer's
+/// ancestors are passed as remaining accounts.
+/// 
 /// # Arguments
 /// * `ctx` - Context containing vault and access grant accounts
 /// * `permission_level` - Level of permission to grant
 /// * `expires_at` - Optional expiration timestamp
+/// * `scope` - Optional restriction to a slice of the vault (None = whole vault)
+/// * `permissions` - Optional `PERMISSION_*` bits (None = bits of `permission_level`)
+/// * `not_before` - Optional time before which the grant is unusable
+/// * `max_uses` - Optional number of writes the grant authorizes before deactivating
 /// 
 /// # Errors
 /// * `ProgramPaused` - If protocol is currently paused
-/// * `CannotGrantToOwner` - If trying to grant access to vault owner
-/// * `InvalidExpiration` - If expiration is in the past
+/// * `VaultFrozen` - If the vault has been frozen by its owner
+/// * `InvalidGrantScope` - If the scope prefix is too long or has invalid tags
+/// * `InvalidMaxUses` - If `max_uses` is zero
+/// * `CannotGrantToOwner` - If trying to grant access to vault owner or oneself
+/// * `AccessNotGranted` - If signer is not the owner and lacks `PERMISSION_GRANT`
+/// * `InvalidPermission` - If no bits are granted, or a non-owner grants bits it lacks
+/// * `InvalidExpiration` - If expiration is in the past or not after `not_before`
+/// * `SubGrantExceedsParent` - If a sub-grant is wider than the issuer's grant
+/// * `GrantDepthExceeded` - If the issuer's grant is already `MAX_GRANT_DEPTH` deep
+/// * `InvalidGrantChain` - If the issuer's ancestors are missing or re-issued
+/// * `UsageLimitedGrant` - If the issuer's grant or one of its ancestors is usage-limited
+/// * `MultisigApprovalRequired` - If the vault has approvers and no approved proposal is given
 pub fn grant_access(
     ctx: Context<GrantAccess>,
     permission_level: PermissionLevel,
     expires_at: Option<i64>,
+    scope: Option<GrantScope>,
+    permissions: Option<u8>,
+    not_before: Option<i64>,
+    max_uses: Option<u32>,
 ) -> Result<()> {
     // Check program is not paused
     require!(
@@ -530,25 +2432,77 @@ pub fn grant_access(
         AgentMemoryError::ProtocolPaused
     );
     
-    let vault = &ctx.accounts.vault;
+    // Check vault is not frozen
+    require!(ctx.accounts.vault.is_active, AgentMemoryError::VaultFrozen);
+    
     let grantee = ctx.accounts.grantee.key();
-    let owner = ctx.accounts.owner.key();
+    let authority = ctx.accounts.authority.key();
     
-    // Validate grantee is not the owner
+    // Validate grantee is not the owner or the granting admin
     require!(
-        grantee != owner,
+        grantee != ctx.accounts.vault.owner && grantee != authority,
         AgentMemoryError::CannotGrantToOwner
     );
     
-    // Validate permission level is not None
-    require!(
-        permission_level != PermissionLevel::None,
-        AgentMemoryError::InvalidPermission
-    );
+    let granted = resolve_permissions(permission_level, permissions)?;
+    
+    if let Some(scope) = &scope {
+        validate_grant_scope(scope)?;
+    }
+    validate_grant_window(not_before, expires_at, max_uses)?;
     
-    let access_grant = &mut ctx.accounts.access_grant;
     let clock = Clock::get()?;
     
+    // Check signer is the owner or a grantee allowed to grant these bits
+    authorize_grant_manager(
+        &ctx.accounts.vault,
+        &authority,
+        ctx.accounts.authority_grant.as_deref(),
+        ctx.remaining_accounts,
+        PERMISSION_GRANT,
+        granted,
+        &[],
+        clock.unix_timestamp,
+    )?;
+    
+    // Grant changes need multisig approval when configured
+    require_approval(
+        &ctx.accounts.vault,
+        ctx.accounts.proposal.as_mut(),
+        &ProposalAction::GrantAccess {
+            grantee,
+            permission_level,
+            expires_at,
+            scope: scope.clone(),
+            permissions,
+            not_before,
+            max_uses,
+        },
+        authority,
+        clock.unix_timestamp,
+    )?;
+    
+    // Grants issued by a grantee are sub-grants no wider than its own
+    let parent = if authority == ctx.accounts.vault.owner {
+        None
+    } else {
+        ctx.accounts.authority_grant.as_ref()
+    };
+    let (scope, not_before, expires_at) = match parent {
+        Some(parent) => {
+            let (scope, not_before, expires_at) =
+                attenuate_sub_grant(parent, expires_at, scope, not_before)?;
+            (Some(scope), not_before, expires_at)
+        }
+        None => (scope, not_before, expires_at),
+    };
+    let parent_grant = parent.map(|parent| parent.key());
+    let parent_granted_at = parent.map_or(0, |parent| parent.granted_at);
+    let depth = parent.map_or(0, |parent| parent.depth + 1);
+    
+    let vault = &mut ctx.accounts.vault;
+    let access_grant = &mut ctx.accounts.access_grant;
+    
     // Validate expiration if provided
     if let Some(exp) = expires_at {
         require!(
@@ -564,48 +2518,651 @@ pub fn grant_access(
     access_grant.vault = vault.key();
     access_grant.grantee = grantee;
     access_grant.permission_level = permission_level;
+    access_grant.permissions = granted;
     access_grant.granted_at = clock.unix_timestamp;
     access_grant.expires_at = expires_at;
+    access_grant.not_before = not_before;
+    access_grant.remaining_uses = max_uses;
     access_grant.is_active = true;
     access_grant.revoked_at = None;
+    access_grant.payer = authority;
+    access_grant.scope = scope.unwrap_or_default();
+    access_grant.parent_grant = parent_grant;
+    access_grant.parent_granted_at = parent_granted_at;
+    access_grant.depth = depth;
+    access_grant.owner_epoch = vault.owner_epoch;
     access_grant.bump = ctx.bumps.access_grant;
     
-    emit!(AccessGranted {
+    vault.active_grant_count = vault
+        .active_grant_count
+        .checked_add(1)
+        .ok_or(AgentMemoryError::Overflow)?;
+    vault.track_open_account()?;
+    vault.record_heartbeat(&authority, clock.unix_timestamp);
+    
+    emit!(AccessGranted {
+        vault: vault.key(),
+        grantee,
+        granted_by: authority,
+        permission_level: permission_level as u8,
+        permissions: granted,
+        granted_at: clock.unix_timestamp,
+        expires_at,
+        not_before,
+        max_uses,
+        scope: access_grant.scope.clone(),
+        parent_grant,
+    });
+    
+    Ok(())
+}
+
+/// Revokes access to a vault
+/// 
+/// Revoking a grant makes all of its sub-grants unusable. Grantees may only
+/// revoke sub-grants issued under their own grant.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing vault and access grant accounts
+/// 
+/// # Errors
+/// * `ProgramPaused` - If protocol is currently paused
+/// * `AccessNotGranted` - If signer is not the owner and lacks `PERMISSION_GRANT`
+/// * `InvalidPermission` - If a non-owner revokes a grant with bits it lacks
+/// * `NotGrantIssuer` - If a non-owner revokes a grant not issued under its own
+/// * `UsageLimitedGrant` - If a non-owner's grant or one of its ancestors is usage-limited
+/// * `MultisigApprovalRequired` - If the vault has approvers and no approved proposal is given
+pub fn revoke_access(ctx: Context<RevokeAccess>) -> Result<()> {
+    // Check program is not paused
+    require!(
+        !ctx.accounts.protocol_config.is_paused,
+        AgentMemoryError::ProtocolPaused
+    );
+    
+    let clock = Clock::get()?;
+    let authority = ctx.accounts.authority.key();
+    
+    // Check signer is the owner or a grantee allowed to manage this grant
+    authorize_grant_manager(
+        &ctx.accounts.vault,
+        &authority,
+        ctx.accounts.authority_grant.as_deref(),
+        ctx.remaining_accounts,
+        PERMISSION_GRANT,
+        ctx.accounts.access_grant.permissions,
+        &[],
+        clock.unix_timestamp,
+    )?;
+    require_grant_issuer(
+        &ctx.accounts.vault,
+        &authority,
+        ctx.accounts.authority_grant.as_ref(),
+        &ctx.accounts.access_grant,
+    )?;
+    
+    // Grant changes need multisig approval when configured
+    require_approval(
+        &ctx.accounts.vault,
+        ctx.accounts.proposal.as_mut(),
+        &ProposalAction::RevokeAccess {
+            grantee: ctx.accounts.access_grant.grantee,
+        },
+        authority,
+        clock.unix_timestamp,
+    )?;
+    
+    let vault = &mut ctx.accounts.vault;
+    let access_grant = &mut ctx.accounts.access_grant;
+    
+    if access_grant.is_active {
+        vault.active_grant_count = vault.active_grant_count.saturating_sub(1);
+    }
+    vault.record_heartbeat(&authority, clock.unix_timestamp);
+    
+    access_grant.is_active = false;
+    access_grant.revoked_at = Some(clock.unix_timestamp);
+    
+    emit!(AccessRevoked {
+        vault: vault.key(),
+        grantee: access_grant.grantee,
+        revoked_by: authority,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Updates an existing access grant
+/// 
+/// Changes the permission level or expiry of a grant, reactivating it if it
+/// had been revoked. Grant PDAs can only be initialized once, so this is the
+/// only way to re-grant a previously revoked grantee.
+/// 
+/// Grantees may only update sub-grants issued under their own grant, within
+/// the limits of `grant_access`.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing vault and access grant accounts
+/// * `permission_level` - New permission level
+/// * `expires_at` - New optional expiration timestamp
+/// * `scope` - New scope, or None to keep the current one
+/// * `permissions` - New `PERMISSION_*` bits (None = bits of `permission_level`)
+/// * `not_before` - New optional start time
+/// * `max_uses` - New usage limit, replacing any remaining uses (None = unlimited)
+/// 
+/// # Errors
+/// * `ProgramPaused` - If protocol is currently paused
+/// * `VaultFrozen` - If the vault has been frozen by its owner
+/// * `InvalidGrantScope` - If the scope prefix is too long or has invalid tags
+/// * `CannotGrantToOwner` - If a grantee tries to update its own grant
+/// * `AccessNotGranted` - If signer is not the owner and lacks `PERMISSION_GRANT`
+/// * `InvalidPermission` - If no bits are granted, or a non-owner touches bits it lacks
+/// * `InvalidExpiration` - If expiration is in the past or not after `not_before`
+/// * `InvalidMaxUses` - If `max_uses` is zero
+/// * `ExpirationTooFar` - If expiration is more than a year away
+/// * `NotGrantIssuer` - If a non-owner updates a grant not issued under its own
+/// * `UsageLimitedGrant` - If a non-owner's grant or one of its ancestors is usage-limited
+/// * `SubGrantExceedsParent` - If a sub-grant would be wider than the issuer's grant
+/// * `MultisigApprovalRequired` - If the vault has approvers and no approved proposal is given
+pub fn update_access(
+    ctx: Context<UpdateAccess>,
+    permission_level: PermissionLevel,
+    expires_at: Option<i64>,
+    scope: Option<GrantScope>,
+    permissions: Option<u8>,
+    not_before: Option<i64>,
+    max_uses: Option<u32>,
+) -> Result<()> {
+    // Check program is not paused
+    require!(
+        !ctx.accounts.protocol_config.is_paused,
+        AgentMemoryError::ProtocolPaused
+    );
+    
+    // Check vault is not frozen
+    require!(ctx.accounts.vault.is_active, AgentMemoryError::VaultFrozen);
+    
+    let grantee = ctx.accounts.access_grant.grantee;
+    let authority = ctx.accounts.authority.key();
+    let old_permission_level = ctx.accounts.access_grant.permission_level;
+    let old_permissions = ctx.accounts.access_grant.permissions;
+    
+    require!(grantee != authority, AgentMemoryError::CannotGrantToOwner);
+    let granted = resolve_permissions(permission_level, permissions)?;
+    
+    if let Some(scope) = &scope {
+        validate_grant_scope(scope)?;
+    }
+    validate_grant_window(not_before, expires_at, max_uses)?;
+    
+    let clock = Clock::get()?;
+    
+    // Check signer may manage both the current and the new bits
+    for bits in [old_permissions, granted] {
+        authorize_grant_manager(
+            &ctx.accounts.vault,
+            &authority,
+            ctx.accounts.authority_grant.as_deref(),
+            ctx.remaining_accounts,
+            PERMISSION_GRANT,
+            bits,
+            &[],
+            clock.unix_timestamp,
+        )?;
+    }
+    require_grant_issuer(
+        &ctx.accounts.vault,
+        &authority,
+        ctx.accounts.authority_grant.as_ref(),
+        &ctx.accounts.access_grant,
+    )?;
+    
+    // Grant changes need multisig approval when configured
+    require_approval(
+        &ctx.accounts.vault,
+        ctx.accounts.proposal.as_mut(),
+        &ProposalAction::UpdateAccess {
+            grant: ctx.accounts.access_grant.key(),
+            permission_level,
+            expires_at,
+            scope: scope.clone(),
+            permissions,
+            not_before,
+            max_uses,
+        },
+        authority,
+        clock.unix_timestamp,
+    )?;
+    
+    // Validate expiration if provided
+    if let Some(exp) = expires_at {
+        require!(
+            exp > clock.unix_timestamp,
+            AgentMemoryError::InvalidExpiration
+        );
+        require!(
+            exp <= clock.unix_timestamp + (365 * 24 * 60 * 60),
+            AgentMemoryError::ExpirationTooFar
+        );
+    }
+    
+    // Sub-grants stay within the issuer's grant and are re-linked to it
+    let parent = if authority == ctx.accounts.vault.owner {
+        None
+    } else {
+        ctx.accounts.authority_grant.as_ref()
+    };
+    let (scope, not_before, expires_at) = match parent {
+        Some(parent) => {
+            let scope = scope.unwrap_or_else(|| ctx.accounts.access_grant.scope.clone());
+            let (scope, not_before, expires_at) =
+                attenuate_sub_grant(parent, expires_at, Some(scope), not_before)?;
+            (Some(scope), not_before, expires_at)
+        }
+        None => (scope, not_before, expires_at),
+    };
+    let parent_granted_at = parent.map(|parent| parent.granted_at);
+    
+    let vault = &mut ctx.accounts.vault;
+    let access_grant = &mut ctx.accounts.access_grant;
+    let old_expires_at = access_grant.expires_at;
+    let old_not_before = access_grant.not_before;
+    let old_remaining_uses = access_grant.remaining_uses;
+    let old_scope = access_grant.scope.clone();
+    let reactivated = !access_grant.is_active;
+    
+    if reactivated {
+        vault.active_grant_count = vault
+            .active_grant_count
+            .checked_add(1)
+            .ok_or(AgentMemoryError::Overflow)?;
+        access_grant.granted_at = clock.unix_timestamp;
+        access_grant.is_active = true;
+        access_grant.revoked_at = None;
+    }
+    access_grant.permission_level = permission_level;
+    access_grant.permissions = granted;
+    access_grant.expires_at = expires_at;
+    access_grant.not_before = not_before;
+    access_grant.remaining_uses = max_uses;
+    if let Some(scope) = scope {
+        access_grant.scope = scope;
+    }
+    if let Some(parent_granted_at) = parent_granted_at {
+        access_grant.parent_granted_at = parent_granted_at;
+    }
+    access_grant.owner_epoch = vault.owner_epoch;
+    vault.record_heartbeat(&authority, clock.unix_timestamp);
+    
+    emit!(AccessUpdated {
+        vault: vault.key(),
+        grantee,
+        updated_by: authority,
+        old_permission_level: old_permission_level as u8,
+        new_permission_level: permission_level as u8,
+        old_permissions,
+        new_permissions: granted,
+        old_expires_at,
+        new_expires_at: expires_at,
+        old_not_before,
+        new_not_before: not_before,
+        old_remaining_uses,
+        new_remaining_uses: max_uses,
+        old_scope,
+        new_scope: access_grant.scope.clone(),
+        reactivated,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Closes a revoked or expired access grant, refunding rent to its payer
+/// 
+/// # Arguments
+/// * `ctx` - Context containing vault and access grant accounts
+/// 
+/// # Errors
+/// * `GrantStillActive` - If the grant is active and has not expired
+pub fn close_access_grant(ctx: Context<CloseAccessGrant>) -> Result<()> {
+    let clock = Clock::get()?;
+    let vault = &mut ctx.accounts.vault;
+    let access_grant = &ctx.accounts.access_grant;
+    
+    require!(
+        !access_grant.is_active || grant_has_expired(access_grant, clock.unix_timestamp),
+        AgentMemoryError::GrantStillActive
+    );
+    
+    if access_grant.is_active {
+        vault.active_grant_count = vault.active_grant_count.saturating_sub(1);
+    }
+    vault.release_open_account();
+    vault.last_heartbeat = clock.unix_timestamp;
+    
+    emit!(AccessGrantClosed {
+        vault: vault.key(),
+        grantee: access_grant.grantee,
+        closed_by: ctx.accounts.owner.key(),
+        payer: access_grant.payer,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Closes an expired access grant, refunding rent to its payer
+/// 
+/// Permissionless so that stale grants can be cleaned up without the owner.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing vault and access grant accounts
+/// 
+/// # Errors
+/// * `GrantStillActive` - If the grant has no expiry or has not expired yet
+pub fn close_expired_access_grant(ctx: Context<CloseExpiredAccessGrant>) -> Result<()> {
+    let clock = Clock::get()?;
+    let vault = &mut ctx.accounts.vault;
+    let access_grant = &ctx.accounts.access_grant;
+    
+    require!(
+        grant_has_expired(access_grant, clock.unix_timestamp),
+        AgentMemoryError::GrantStillActive
+    );
+    
+    if access_grant.is_active {
+        vault.active_grant_count = vault.active_grant_count.saturating_sub(1);
+    }
+    vault.release_open_account();
+    
+    emit!(AccessGrantClosed {
+        vault: vault.key(),
+        grantee: access_grant.grantee,
+        closed_by: ctx.accounts.caller.key(),
+        payer: access_grant.payer,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+fn grant_has_expired(grant: &AccessGrant, now: i64) -> bool {
+    grant.expires_at.is_some_and(|exp| now >= exp)
+}
+
+/// Records a grant-based read of a memory shard
+/// 
+/// Reads happen off-chain, so grantees with a usage-limited grant call this
+/// to spend one use before fetching content. The program cannot observe
+/// reads, so read limits are voluntary: a client that skips this call reads
+/// without spending uses. Only writes are enforced on-chain. The ancestors of
+/// a sub-grant are passed as remaining accounts.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing vault, memory shard and access grant accounts
+/// 
+/// # Errors
+/// * `AccessNotGranted` - If the grant is inactive or lacks `PERMISSION_READ`
+/// * `AccessNotYetValid` - If the grant's start time has not been reached
+/// * `AccessExpired` - If the grant has expired
+/// * `GrantScopeViolation` - If the shard is outside the grant scope
+/// * `InvalidGrantChain` - If a sub-grant's ancestors are missing or re-issued
+/// * `UsageLimitedGrant` - If an ancestor of a sub-grant is usage-limited
+pub fn record_memory_read(ctx: Context<RecordMemoryRead>) -> Result<()> {
+    let clock = Clock::get()?;
+    let shard = &ctx.accounts.memory_shard;
+    let shards = [(shard.key.as_str(), &shard.metadata)];
+    let grant = &mut ctx.accounts.access_grant;
+    
+    check_owner_epoch(&ctx.accounts.vault, grant.owner_epoch)?;
+    check_access_grant(grant, PERMISSION_READ, clock.unix_timestamp)?;
+    require!(
+        grant.scope.allows(&shard.key, &shard.metadata),
+        AgentMemoryError::GrantScopeViolation
+    );
+    check_grant_ancestors(
+        grant,
+        ctx.remaining_accounts,
+        PERMISSION_READ,
+        &shards,
+        clock.unix_timestamp,
+    )?;
+    consume_grant_use(&mut ctx.accounts.vault, grant, clock.unix_timestamp)?;
+    
+    emit!(MemoryAccessLogged {
+        memory: shard.key(),
+        accessor: ctx.accounts.authority.key(),
+        access_type: AccessType::Read as u8,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Shares a single memory shard with a grantee
+/// 
+/// Creates or replaces the `MemoryAcl` entry for `(shard, grantee)`. Entries
+/// are checked before the grantee's vault-level grant. An entry shared by a
+/// grantee is linked to the issuer's grant: it expires no later than that
+/// grant and stops working once it is revoked or narrowed. Only the issuer
+/// or the vault owner may replace an entry.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing vault, memory shard and ACL accounts
+/// * `permission_level` - Level of permission on the shard
+/// * `expires_at` - Optional expiration timestamp
+/// * `permissions` - Optional `PERMISSION_*` bits (None = bits of `permission_level`)
+/// 
+/// # Errors
+/// * `ProgramPaused` - If protocol is currently paused
+/// * `VaultFrozen` - If the vault has been frozen by its owner
+/// * `CannotGrantToOwner` - If sharing with the vault owner or oneself
+/// * `AccessNotGranted` - If signer is not the owner and lacks `PERMISSION_SHARE`
+/// * `InvalidPermission` - If no bits are granted, or a non-owner shares bits it lacks
+/// * `GrantScopeViolation` - If the shard is outside the scope of the signer's grant or its ancestors
+/// * `InvalidGrantChain` - If the signer's ancestor grants are missing or re-issued
+/// * `UsageLimitedGrant` - If the signer's grant or one of its ancestors is usage-limited
+/// * `NotGrantIssuer` - If replacing an entry issued by someone else
+/// * `SubGrantExceedsParent` - If the entry outlives the signer's grant
+/// * `InvalidExpiration` - If expiration is in the past
+/// * `MultisigApprovalRequired` - If the vault has approvers and no approved proposal is given
+pub fn set_memory_acl(
+    ctx: Context<SetMemoryAcl>,
+    permission_level: PermissionLevel,
+    expires_at: Option<i64>,
+    permissions: Option<u8>,
+) -> Result<()> {
+    // Check program is not paused
+    require!(
+        !ctx.accounts.protocol_config.is_paused,
+        AgentMemoryError::ProtocolPaused
+    );
+    
+    // Check vault is not frozen
+    require!(ctx.accounts.vault.is_active, AgentMemoryError::VaultFrozen);
+    
+    let grantee = ctx.accounts.grantee.key();
+    let authority = ctx.accounts.authority.key();
+    let memory = ctx.accounts.memory_shard.key();
+    
+    require!(
+        grantee != ctx.accounts.vault.owner && grantee != authority,
+        AgentMemoryError::CannotGrantToOwner
+    );
+    let granted = resolve_permissions(permission_level, permissions)?;
+    
+    let clock = Clock::get()?;
+    
+    // Only the issuer of an existing entry or the owner may replace it
+    let existing = &ctx.accounts.memory_acl;
+    require!(
+        existing.payer == Pubkey::default()
+            || authority == ctx.accounts.vault.owner
+            || existing.granted_by == authority,
+        AgentMemoryError::NotGrantIssuer
+    );
+    
+    // Check signer is the owner or a grantee allowed to share this shard
+    let shard = &ctx.accounts.memory_shard;
+    let shards = [(shard.key.as_str(), &shard.metadata)];
+    authorize_grant_manager(
+        &ctx.accounts.vault,
+        &authority,
+        ctx.accounts.authority_grant.as_deref(),
+        ctx.remaining_accounts,
+        PERMISSION_SHARE,
+        granted,
+        &shards,
+        clock.unix_timestamp,
+    )?;
+    
+    // Entries shared by a grantee are bounded by and linked to its grant
+    let issuer = if authority == ctx.accounts.vault.owner {
+        None
+    } else {
+        ctx.accounts.authority_grant.as_ref()
+    };
+    let mut expires_at = expires_at;
+    if let Some(issuer) = issuer {
+        require!(
+            issuer.scope.allows(&shard.key, &shard.metadata),
+            AgentMemoryError::GrantScopeViolation
+        );
+        expires_at = expires_at.or(issuer.expires_at);
+        let within_expiry = match issuer.expires_at {
+            Some(max) => expires_at.is_some_and(|exp| exp <= max),
+            None => true,
+        };
+        require!(within_expiry, AgentMemoryError::SubGrantExceedsParent);
+    }
+    let issuer_grant = issuer.map(|issuer| issuer.key());
+    let issuer_granted_at = issuer.map_or(0, |issuer| issuer.granted_at);
+    let issuer_depth = issuer.map_or(0, |issuer| issuer.depth + 1);
+    
+    // Sharing needs multisig approval when configured
+    require_approval(
+        &ctx.accounts.vault,
+        ctx.accounts.proposal.as_mut(),
+        &ProposalAction::SetMemoryAcl {
+            memory,
+            grantee,
+            permission_level,
+            expires_at,
+            permissions,
+        },
+        authority,
+        clock.unix_timestamp,
+    )?;
+    
+    // Validate expiration if provided
+    if let Some(exp) = expires_at {
+        require!(
+            exp > clock.unix_timestamp,
+            AgentMemoryError::InvalidExpiration
+        );
+        require!(
+            exp <= clock.unix_timestamp + (365 * 24 * 60 * 60),
+            AgentMemoryError::ExpirationTooFar
+        );
+    }
+    
+    let vault = &mut ctx.accounts.vault;
+    let memory_acl = &mut ctx.accounts.memory_acl;
+    
+    if memory_acl.payer == Pubkey::default() {
+        memory_acl.payer = authority;
+        memory_acl.bump = ctx.bumps.memory_acl;
+    }
+    // Entries are keyed by shard, so one left by a vault the shards were
+    // migrated from is taken over and counted here
+    if memory_acl.vault != vault.key() {
+        vault.track_open_account()?;
+    }
+    memory_acl.memory = memory;
+    memory_acl.vault = vault.key();
+    memory_acl.grantee = grantee;
+    memory_acl.permission_level = permission_level;
+    memory_acl.permissions = granted;
+    memory_acl.memory_created_at = ctx.accounts.memory_shard.created_at;
+    memory_acl.granted_by = authority;
+    memory_acl.granted_at = clock.unix_timestamp;
+    memory_acl.expires_at = expires_at;
+    memory_acl.issuer_grant = issuer_grant;
+    memory_acl.issuer_granted_at = issuer_granted_at;
+    memory_acl.issuer_depth = issuer_depth;
+    memory_acl.owner_epoch = vault.owner_epoch;
+    
+    vault.record_heartbeat(&authority, clock.unix_timestamp);
+    
+    emit!(MemoryAclUpdated {
         vault: vault.key(),
+        memory,
         grantee,
-        granted_by: owner,
-        permission_level: permission_level as u8,
-        granted_at: clock.unix_timestamp,
+        updated_by: authority,
+        permission_level: Some(permission_level as u8),
+        permissions: granted,
         expires_at,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    emit!(MemoryAccessLogged {
+        memory,
+        accessor: authority,
+        access_type: AccessType::Share as u8,
+        timestamp: clock.unix_timestamp,
     });
     
     Ok(())
 }
 
-/// Revokes access to a vault
+/// Removes a shard-level ACL entry, refunding rent to its payer
 /// 
 /// # Arguments
-/// * `ctx` - Context containing vault and access grant accounts
+/// * `ctx` - Context containing vault and ACL accounts
 /// 
 /// # Errors
-/// * `ProgramPaused` - If protocol is currently paused
-pub fn revoke_access(ctx: Context<RevokeAccess>) -> Result<()> {
-    // Check program is not paused
+/// * `NotGrantIssuer` - If signer is neither the owner nor the entry's issuer
+/// * `MultisigApprovalRequired` - If the vault has approvers and no approved proposal is given
+pub fn remove_memory_acl(ctx: Context<RemoveMemoryAcl>) -> Result<()> {
+    let clock = Clock::get()?;
+    let authority = ctx.accounts.authority.key();
+    let memory = ctx.accounts.memory_acl.memory;
+    let grantee = ctx.accounts.memory_acl.grantee;
+    
+    // Only the owner or whoever issued the entry may remove it
     require!(
-        !ctx.accounts.protocol_config.is_paused,
-        AgentMemoryError::ProtocolPaused
+        authority == ctx.accounts.vault.owner || authority == ctx.accounts.memory_acl.granted_by,
+        AgentMemoryError::NotGrantIssuer
     );
     
-    let access_grant = &mut ctx.accounts.access_grant;
-    let clock = Clock::get()?;
+    // Unsharing needs multisig approval when configured
+    require_approval(
+        &ctx.accounts.vault,
+        ctx.accounts.proposal.as_mut(),
+        &ProposalAction::RemoveMemoryAcl { memory, grantee },
+        authority,
+        clock.unix_timestamp,
+    )?;
     
-    access_grant.is_active = false;
-    access_grant.revoked_at = Some(clock.unix_timestamp);
+    let vault = &mut ctx.accounts.vault;
+    vault.release_open_account();
+    vault.record_heartbeat(&authority, clock.unix_timestamp);
     
-    emit!(AccessRevoked {
-        vault: ctx.accounts.vault.key(),
-        grantee: access_grant.grantee,
-        revoked_by: ctx.accounts.owner.key(),
+    emit!(MemoryAclUpdated {
+        vault: vault.key(),
+        memory,
+        grantee,
+        updated_by: authority,
+        permission_level: None,
+        permissions: 0,
+        expires_at: None,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    emit!(MemoryAccessLogged {
+        memory,
+        accessor: authority,
+        access_type: AccessType::Share as u8,
         timestamp: clock.unix_timestamp,
     });
     
@@ -625,6 +3182,7 @@ pub fn revoke_access(ctx: Context<RevokeAccess>) -> Result<()> {
 /// * `is_public` - Optional visibility setting
 /// 
 /// # Errors
+/// * `AlreadyMigrated` - If the profile has been migrated to a new agent key
 /// * `NameTooLong` - If name exceeds max length
 /// * `TooManyCapabilities` - If too many capabilities
 pub fn update_profile(
@@ -637,6 +3195,8 @@ pub fn update_profile(
     let clock = Clock::get()?;
     let mut updated_fields: u8 = 0;
     
+    require!(profile.migrated_to.is_none(), AgentMemoryError::AlreadyMigrated);
+    
     if let Some(new_name) = name {
         require!(!new_name.is_empty(), AgentMemoryError::EmptyName);
         require!(
@@ -686,11 +3246,14 @@ pub fn update_profile(
 /// * `ctx` - Context containing profile account
 /// 
 /// # Errors
+/// * `AlreadyMigrated` - If the profile has been migrated to a new agent key
 /// * `TaskRateLimitExceeded` - If called too frequently
 pub fn record_task(ctx: Context<RecordTask>) -> Result<()> {
     let profile = &mut ctx.accounts.agent_profile;
     let clock = Clock::get()?;
     
+    require!(profile.migrated_to.is_none(), AgentMemoryError::AlreadyMigrated);
+    
     // Check rate limit
     require!(
         clock.unix_timestamp - profile.last_task_at >= TASK_RATE_LIMIT_SECONDS,
@@ -753,15 +3316,22 @@ pub fn create_sharing_group(
     let clock = Clock::get()?;
     
     group.creator = ctx.accounts.owner.key();
+    group.pending_creator = None;
     group.vault = ctx.accounts.vault.key();
+    group.seed_name = name.clone();
     group.name = name;
     group.description = description;
-    group.members = Vec::new();
     group.member_count = 0;
+    group.key_epoch = 0;
+    group.open_key_epochs = 0;
+    group.key_rotation_pending = false;
     group.created_at = clock.unix_timestamp;
     group.updated_at = clock.unix_timestamp;
     group.is_active = true;
+    group.owner_epoch = ctx.accounts.vault.owner_epoch;
     group.bump = ctx.bumps.sharing_group;
+    ctx.accounts.vault.track_open_account()?;
+    ctx.accounts.vault.last_heartbeat = clock.unix_timestamp;
     
     emit!(SharingGroupCreated {
         group: group.key(),
@@ -774,6 +3344,335 @@ pub fn create_sharing_group(
     Ok(())
 }
 
+/// Adds a member to a sharing group
+/// 
+/// Each member gets its own `GroupMember` PDA, so groups are not size-capped.
+/// Membership is vault access, so only the vault owner manages members.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing vault, sharing group and member record accounts
+/// * `member` - Agent to add
+/// * `permission` - Permission level of the member
+/// * `permissions` - Optional `PERMISSION_*` bits (None = bits of `permission`)
+/// 
+/// # Errors
+/// * `UnauthorizedOwner` - If signer is not the vault owner
+/// * `VaultFrozen` - If the vault has been frozen by its owner
+/// * `MemberAlreadyExists` - If the agent is the vault owner
+/// * `InvalidPermission` - If no permission bits are granted
+/// * `MultisigApprovalRequired` - If the vault has approvers and no approved proposal is given
+pub fn add_group_member(
+    ctx: Context<AddGroupMember>,
+    member: Pubkey,
+    permission: PermissionLevel,
+    permissions: Option<u8>,
+) -> Result<()> {
+    require!(ctx.accounts.vault.is_active, AgentMemoryError::VaultFrozen);
+    require!(
+        member != ctx.accounts.vault.owner,
+        AgentMemoryError::MemberAlreadyExists
+    );
+    let granted = resolve_permissions(permission, permissions)?;
+    
+    let clock = Clock::get()?;
+    
+    // Membership changes need multisig approval when configured
+    require_approval(
+        &ctx.accounts.vault,
+        ctx.accounts.proposal.as_mut(),
+        &ProposalAction::AddGroupMember {
+            group: ctx.accounts.sharing_group.key(),
+            member,
+            permission,
+            permissions,
+        },
+        ctx.accounts.owner.key(),
+        clock.unix_timestamp,
+    )?;
+    
+    let group = &mut ctx.accounts.sharing_group;
+    let group_member = &mut ctx.accounts.group_member;
+    
+    group_member.group = group.key();
+    group_member.member = member;
+    group_member.permission = permission;
+    group_member.permissions = granted;
+    group_member.joined_at = clock.unix_timestamp;
+    group_member.payer = ctx.accounts.owner.key();
+    group_member.bump = ctx.bumps.group_member;
+    
+    group.member_count = group
+        .member_count
+        .checked_add(1)
+        .ok_or(AgentMemoryError::Overflow)?;
+    group.updated_at = clock.unix_timestamp;
+    ctx.accounts.vault.last_heartbeat = clock.unix_timestamp;
+    
+    emit!(GroupMemberAdded {
+        group: group.key(),
+        member,
+        permission: permission as u8,
+        permissions: granted,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Removes a member from a sharing group and closes its record
+/// 
+/// # Arguments
+/// * `ctx` - Context containing vault, sharing group and member record accounts
+/// 
+/// # Errors
+/// * `UnauthorizedOwner` - If signer is not the vault owner
+/// * `MemberNotFound` - If the record does not belong to this group
+pub fn remove_group_member(ctx: Context<RemoveGroupMember>) -> Result<()> {
+    let clock = Clock::get()?;
+    ctx.accounts.vault.last_heartbeat = clock.unix_timestamp;
+    
+    remove_member(
+        &mut ctx.accounts.sharing_group,
+        ctx.accounts.group_member.member,
+        clock.unix_timestamp,
+    )
+}
+
+/// Removes the signer from a sharing group and closes its record
+/// 
+/// # Arguments
+/// * `ctx` - Context containing sharing group and member record accounts
+/// 
+/// # Errors
+/// * `MemberNotFound` - If the signer is not a member
+pub fn leave_group(ctx: Context<LeaveGroup>) -> Result<()> {
+    let clock = Clock::get()?;
+    
+    remove_member(
+        &mut ctx.accounts.sharing_group,
+        ctx.accounts.member.key(),
+        clock.unix_timestamp,
+    )
+}
+
+fn remove_member(group: &mut Account<SharingGroup>, member: Pubkey, now: i64) -> Result<()> {
+    group.member_count = group.member_count.saturating_sub(1);
+    // The removed member holds the current group key
+    group.key_rotation_pending |= group.key_epoch > 0;
+    group.updated_at = now;
+    
+    emit!(GroupMemberRemoved {
+        group: group.key(),
+        member,
+        timestamp: now,
+    });
+    
+    Ok(())
+}
+
+/// Updates a sharing group's name and description
+/// 
+/// The group PDA stays derived from the original name (`seed_name`).
+/// 
+/// # Arguments
+/// * `ctx` - Context containing sharing group account
+/// * `name` - New display name
+/// * `description` - New description
+/// 
+/// # Errors
+/// * `UnauthorizedOwner` - If signer is not the vault owner
+/// * `EmptyGroupName` - If name is empty
+/// * `GroupNameTooLong` - If name is too long
+/// * `GroupDescTooLong` - If description is too long
+pub fn update_sharing_group(
+    ctx: Context<ManageSharingGroup>,
+    name: Option<String>,
+    description: Option<String>,
+) -> Result<()> {
+    let group = &mut ctx.accounts.sharing_group;
+    let clock = Clock::get()?;
+    
+    if let Some(new_name) = name {
+        require!(!new_name.is_empty(), AgentMemoryError::EmptyGroupName);
+        require!(
+            new_name.len() <= MAX_GROUP_NAME_LENGTH,
+            AgentMemoryError::GroupNameTooLong
+        );
+        group.name = new_name;
+    }
+    
+    if let Some(new_description) = description {
+        require!(
+            new_description.len() <= MAX_GROUP_DESC_LENGTH,
+            AgentMemoryError::GroupDescTooLong
+        );
+        group.description = new_description;
+    }
+    
+    group.updated_at = clock.unix_timestamp;
+    ctx.accounts.vault.last_heartbeat = clock.unix_timestamp;
+    
+    emit!(SharingGroupUpdated {
+        group: group.key(),
+        name: group.name.clone(),
+        description: group.description.clone(),
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Activates or archives a sharing group
+/// 
+/// Members of an inactive group lose group-based vault access until it is reactivated.
+/// Activating a group also re-confirms it after a change of vault owner.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing sharing group account
+/// * `is_active` - New active state
+/// 
+/// # Errors
+/// * `UnauthorizedOwner` - If signer is not the vault owner
+pub fn set_group_active(ctx: Context<ManageSharingGroup>, is_active: bool) -> Result<()> {
+    let group = &mut ctx.accounts.sharing_group;
+    let clock = Clock::get()?;
+    
+    group.is_active = is_active;
+    if is_active {
+        group.owner_epoch = ctx.accounts.vault.owner_epoch;
+    }
+    group.updated_at = clock.unix_timestamp;
+    ctx.accounts.vault.last_heartbeat = clock.unix_timestamp;
+    
+    emit!(SharingGroupStatusChanged {
+        group: group.key(),
+        is_active,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Proposes a new sharing group creator
+/// 
+/// The creator is attribution only; the group stays managed by the vault owner.
+/// The transfer completes when `new_creator` calls `accept_group_creator`.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing sharing group account
+/// * `new_creator` - Proposed creator
+/// 
+/// # Errors
+/// * `UnauthorizedOwner` - If signer is not the vault owner
+/// * `InvalidNewCreator` - If `new_creator` is the current creator
+pub fn transfer_group_creator(ctx: Context<ManageSharingGroup>, new_creator: Pubkey) -> Result<()> {
+    let group = &mut ctx.accounts.sharing_group;
+    let clock = Clock::get()?;
+    
+    require!(
+        new_creator != group.creator && new_creator != Pubkey::default(),
+        AgentMemoryError::InvalidNewCreator
+    );
+    
+    group.pending_creator = Some(new_creator);
+    group.updated_at = clock.unix_timestamp;
+    ctx.accounts.vault.last_heartbeat = clock.unix_timestamp;
+    
+    emit!(GroupCreatorProposed {
+        group: group.key(),
+        creator: group.creator,
+        pending_creator: group.pending_creator,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Cancels a pending sharing group creator transfer
+/// 
+/// # Arguments
+/// * `ctx` - Context containing sharing group account
+/// 
+/// # Errors
+/// * `UnauthorizedOwner` - If signer is not the vault owner
+/// * `NoPendingCreator` - If no transfer is pending
+pub fn cancel_group_creator_transfer(ctx: Context<ManageSharingGroup>) -> Result<()> {
+    let group = &mut ctx.accounts.sharing_group;
+    let clock = Clock::get()?;
+    
+    require!(group.pending_creator.is_some(), AgentMemoryError::NoPendingCreator);
+    
+    group.pending_creator = None;
+    group.updated_at = clock.unix_timestamp;
+    ctx.accounts.vault.last_heartbeat = clock.unix_timestamp;
+    
+    emit!(GroupCreatorProposed {
+        group: group.key(),
+        creator: group.creator,
+        pending_creator: None,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Accepts a pending sharing group creator transfer
+/// 
+/// # Arguments
+/// * `ctx` - Context containing sharing group account
+/// 
+/// # Errors
+/// * `NoPendingCreator` - If signer is not the pending creator
+pub fn accept_group_creator(ctx: Context<AcceptGroupCreator>) -> Result<()> {
+    let group = &mut ctx.accounts.sharing_group;
+    let new_creator = ctx.accounts.new_creator.key();
+    let old_creator = group.creator;
+    let clock = Clock::get()?;
+    
+    group.creator = new_creator;
+    group.pending_creator = None;
+    group.updated_at = clock.unix_timestamp;
+    
+    emit!(GroupCreatorTransferred {
+        group: group.key(),
+        old_creator,
+        new_creator,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Closes a sharing group and returns its rent to the vault owner
+/// 
+/// Its `GroupKeyEpoch` accounts must be closed first (`close_group_key_epoch`),
+/// so a group recreated under the same name can start again at epoch 1.
+/// 
+/// # Arguments
+/// * `ctx` - Context containing sharing group account
+/// 
+/// # Errors
+/// * `UnauthorizedOwner` - If signer is not the vault owner
+/// * `GroupNotEmpty` - If any member remains
+/// * `GroupKeysOpen` - If any group key epoch is still open
+pub fn close_sharing_group(ctx: Context<CloseSharingGroup>) -> Result<()> {
+    let group = &ctx.accounts.sharing_group;
+    let clock = Clock::get()?;
+    
+    require!(group.member_count == 0, AgentMemoryError::GroupNotEmpty);
+    require!(group.open_key_epochs == 0, AgentMemoryError::GroupKeysOpen);
+    ctx.accounts.vault.release_open_account();
+    ctx.accounts.vault.last_heartbeat = clock.unix_timestamp;
+    
+    emit!(SharingGroupClosed {
+        group: group.key(),
+        creator: group.creator,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
 /// ============================================================================
 /// PROTOCOL CONFIG
 /// ============================================================================
@@ -905,3 +3804,263 @@ pub fn transfer_admin(ctx: Context<TransferAdmin>, new_admin: Pubkey) -> Result<
     
     Ok(())
 }
+    
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn parent_grant() -> AccessGrant {
+        AccessGrant {
+            vault: Pubkey::new_unique(),
+            grantee: Pubkey::new_unique(),
+            permission_level: PermissionLevel::Write,
+            permissions: PERMISSION_READ | PERMISSION_CREATE | PERMISSION_GRANT,
+            granted_at: 100,
+            expires_at: Some(1_000),
+            not_before: Some(200),
+            remaining_uses: None,
+            is_active: true,
+            revoked_at: None,
+            payer: Pubkey::new_unique(),
+            scope: GrantScope {
+                key_prefix: "notes/".to_string(),
+                memory_types: MemoryType::Task.mask() | MemoryType::Learning.mask(),
+                required_tags: vec![7],
+            },
+            parent_grant: None,
+            parent_granted_at: 0,
+            depth: 0,
+            owner_epoch: 0,
+            bump: 255,
+        }
+    }
+    
+    fn grant_account(key: Pubkey, grant: &AccessGrant) -> AccountInfo<'static> {
+        let mut data = Vec::new();
+        grant.try_serialize(&mut data).unwrap();
+        AccountInfo::new(
+            Box::leak(Box::new(key)),
+            false,
+            false,
+            Box::leak(Box::new(0)),
+            Box::leak(data.into_boxed_slice()),
+            &crate::ID,
+            false,
+            0,
+        )
+    }
+    
+    fn child_of(key: Pubkey, parent: &AccessGrant) -> AccessGrant {
+        AccessGrant {
+            grantee: Pubkey::new_unique(),
+            parent_grant: Some(key),
+            parent_granted_at: parent.granted_at,
+            depth: parent.depth + 1,
+            ..parent.clone()
+        }
+    }
+    
+    fn metadata(memory_type: MemoryType, tags: [u8; 8]) -> MemoryMetadata {
+        MemoryMetadata {
+            memory_type,
+            importance: 50,
+            tags,
+            ipfs_cid: None,
+        }
+    }
+    
+    fn error_code<T>(result: Result<T>) -> u32 {
+        match result {
+            Err(Error::AnchorError(error)) => error.error_code_number,
+            _ => panic!("expected an Anchor error"),
+        }
+    }
+    
+    #[test]
+    fn sub_grant_inherits_unset_limits() {
+        let parent = parent_grant();
+    
+        let (scope, not_before, expires_at) = attenuate_sub_grant(&parent, None, None, None).unwrap();
+    
+        assert!(scope == parent.scope);
+        assert_eq!(not_before, Some(200));
+        assert_eq!(expires_at, Some(1_000));
+    }
+    
+    #[test]
+    fn sub_grant_may_narrow_parent() {
+        let parent = parent_grant();
+        let narrower = GrantScope {
+            key_prefix: "notes/work/".to_string(),
+            memory_types: MemoryType::Task.mask(),
+            required_tags: vec![7, 9],
+        };
+    
+        let (scope, not_before, expires_at) =
+            attenuate_sub_grant(&parent, Some(900), Some(narrower.clone()), Some(300)).unwrap();
+    
+        assert!(scope == narrower);
+        assert_eq!(not_before, Some(300));
+        assert_eq!(expires_at, Some(900));
+    }
+    
+    #[test]
+    fn sub_grant_cannot_widen_parent() {
+        let parent = parent_grant();
+        let exceeds = u32::from(AgentMemoryError::SubGrantExceedsParent);
+        let wider_scopes = [
+            GrantScope { key_prefix: "not".to_string(), ..parent.scope.clone() },
+            GrantScope { memory_types: 0, ..parent.scope.clone() },
+            GrantScope { required_tags: vec![], ..parent.scope.clone() },
+        ];
+    
+        for scope in wider_scopes {
+            assert_eq!(error_code(attenuate_sub_grant(&parent, None, Some(scope), None)), exceeds);
+        }
+        assert_eq!(error_code(attenuate_sub_grant(&parent, Some(1_001), None, None)), exceeds);
+        assert_eq!(error_code(attenuate_sub_grant(&parent, None, None, Some(199))), exceeds);
+    }
+    
+    #[test]
+    fn sub_grant_stops_at_max_depth() {
+        let mut parent = parent_grant();
+        parent.depth = MAX_GRANT_DEPTH;
+        assert_eq!(
+            error_code(attenuate_sub_grant(&parent, None, None, None)),
+            u32::from(AgentMemoryError::GrantDepthExceeded)
+        );
+    }
+    
+    #[test]
+    fn chain_accepts_live_ancestors_in_order() {
+        let root = parent_grant();
+        let (root_key, parent_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let parent = child_of(root_key, &root);
+        let child = child_of(parent_key, &parent);
+        let ancestors = [grant_account(parent_key, &parent), grant_account(root_key, &root)];
+        let shard = metadata(MemoryType::Task, [7, 0, 0, 0, 0, 0, 0, 0]);
+    
+        check_grant_ancestors(&child, &ancestors, PERMISSION_READ, &[("notes/a", &shard)], 500).unwrap();
+        check_grant_ancestors(&root, &[], PERMISSION_READ, &[], 500).unwrap();
+    }
+    
+    #[test]
+    fn chain_needs_every_ancestor_in_order() {
+        let root = parent_grant();
+        let (root_key, parent_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let parent = child_of(root_key, &root);
+        let child = child_of(parent_key, &parent);
+        let invalid = u32::from(AgentMemoryError::InvalidGrantChain);
+    
+        let missing = [grant_account(parent_key, &parent)];
+        assert_eq!(error_code(check_grant_ancestors(&child, &missing, PERMISSION_READ, &[], 500)), invalid);
+        let swapped = [grant_account(root_key, &root), grant_account(parent_key, &parent)];
+        assert_eq!(error_code(check_grant_ancestors(&child, &swapped, PERMISSION_READ, &[], 500)), invalid);
+        let mut foreign = parent_grant();
+        foreign.vault = Pubkey::new_unique();
+        let other_vault = [grant_account(root_key, &foreign)];
+        assert_eq!(error_code(check_grant_ancestors(&parent, &other_vault, PERMISSION_READ, &[], 500)), invalid);
+    }
+    
+    #[test]
+    fn chain_rejects_reissued_revoked_or_stale_ancestor() {
+        let root = parent_grant();
+        let key = Pubkey::new_unique();
+        let child = child_of(key, &root);
+        let check = |ancestor: AccessGrant| {
+            error_code(check_grant_ancestors(&child, &[grant_account(key, &ancestor)], PERMISSION_READ, &[], 500))
+        };
+    
+        assert_eq!(
+            check(AccessGrant { granted_at: 150, ..root.clone() }),
+            u32::from(AgentMemoryError::InvalidGrantChain)
+        );
+        assert_eq!(
+            check(AccessGrant { is_active: false, ..root.clone() }),
+            u32::from(AgentMemoryError::AccessNotGranted)
+        );
+        assert_eq!(
+            check(AccessGrant { expires_at: Some(400), ..root.clone() }),
+            u32::from(AgentMemoryError::AccessExpired)
+        );
+        assert_eq!(
+            check(AccessGrant { owner_epoch: 1, ..root.clone() }),
+            u32::from(AgentMemoryError::StaleOwnerEpoch)
+        );
+    }
+    
+    #[test]
+    fn chain_applies_ancestor_bits_and_scope() {
+        let mut root = parent_grant();
+        root.permissions = PERMISSION_READ;
+        let key = Pubkey::new_unique();
+        let child = AccessGrant {
+            permissions: PERMISSION_READ | PERMISSION_CREATE,
+            scope: GrantScope::default(),
+            ..child_of(key, &root)
+        };
+        let ancestors = [grant_account(key, &root)];
+        let outside = metadata(MemoryType::System, [7, 0, 0, 0, 0, 0, 0, 0]);
+    
+        assert_eq!(
+            error_code(check_grant_ancestors(&child, &ancestors, PERMISSION_CREATE, &[], 500)),
+            u32::from(AgentMemoryError::AccessNotGranted)
+        );
+        assert_eq!(
+            error_code(check_grant_ancestors(&child, &ancestors, PERMISSION_READ, &[("notes/a", &outside)], 500)),
+            u32::from(AgentMemoryError::GrantScopeViolation)
+        );
+    }
+    
+    #[test]
+    fn chain_rejects_usage_limited_ancestor() {
+        let mut parent = parent_grant();
+        parent.remaining_uses = Some(5);
+        let key = Pubkey::new_unique();
+        let child = child_of(key, &parent);
+        let ancestors = [grant_account(key, &parent)];
+    
+        assert_eq!(
+            error_code(check_grant_ancestors(&child, &ancestors, PERMISSION_READ, &[], 500)),
+            u32::from(AgentMemoryError::UsageLimitedGrant)
+        );
+    }
+    
+    #[test]
+    fn usage_limited_grant_cannot_manage_access() {
+        let vault = crate::tests::vault();
+        let mut grant = parent_grant();
+        grant.remaining_uses = Some(5);
+        let manage = |grant: &AccessGrant| {
+            authorize_grant_manager(&vault, &grant.grantee, Some(grant), &[], PERMISSION_GRANT, PERMISSION_READ, &[], 500)
+        };
+    
+        assert_eq!(error_code(manage(&grant)), u32::from(AgentMemoryError::UsageLimitedGrant));
+        grant.remaining_uses = None;
+        manage(&grant).unwrap();
+    }
+    
+    #[test]
+    fn quota_only_rejects_growth_past_limit() {
+        assert!(!exceeds_quota(5, 1_000, 0));
+        assert!(!exceeds_quota(5, 10, 10));
+        assert!(exceeds_quota(10, 11, 10));
+        assert!(exceeds_quota(12, 13, 10));
+    }
+    
+    #[test]
+    fn over_quota_vault_accepts_shrinking_writes() {
+        assert!(!exceeds_quota(12, 12, 10));
+        assert!(!exceeds_quota(12, 11, 10));
+    }
+    
+    #[test]
+    fn thresholds_fire_once_when_crossed() {
+        assert!(crosses_threshold(79, 80, 100, 80));
+        assert!(crosses_threshold(50, 100, 100, 80));
+        assert!(!crosses_threshold(80, 90, 100, 80));
+        assert!(!crosses_threshold(90, 70, 100, 80));
+        assert!(!crosses_threshold(0, 1_000, 0, 80));
+    }
+}
+    
\ No newline at end of file
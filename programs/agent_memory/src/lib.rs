@@ -8,10 +8,20 @@ declare_id!("Mem1oWL98HnWm9aN4rXY37EL4XgFj5Avq2zA26Zf9yq");
 pub mod error;
 pub mod instructions;
 pub mod identity_binding;
+pub mod snapshot;
+pub mod multisig;
+pub mod recovery;
+pub mod inheritance;
+pub mod group_keys;
 
 pub use error::*;
 pub use instructions::*;
 pub use identity_binding::*;
+pub use snapshot::*;
+pub use multisig::*;
+pub use recovery::*;
+pub use inheritance::*;
+pub use group_keys::*;
 
 // ============================================================================
 // PROGRAM MODULE
@@ -29,6 +39,112 @@ pub mod agent_memory {
         instructions::initialize_vault(ctx, encryption_pubkey)
     }
 
+    /// Closes an empty vault and its agent profile, returning rent to the owner
+    pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
+        instructions::close_vault(ctx)
+    }
+
+    /// Freezes or unfreezes a vault
+    pub fn set_vault_active(
+        ctx: Context<SetVaultActive>,
+        active: bool,
+    ) -> Result<()> {
+        instructions::set_vault_active(ctx, active)
+    }
+
+    /// Proposes a new vault owner (step one of ownership transfer)
+    pub fn propose_vault_owner(
+        ctx: Context<ProposeVaultOwner>,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        instructions::propose_vault_owner(ctx, new_owner)
+    }
+
+    /// Cancels a pending vault ownership transfer
+    pub fn cancel_vault_owner_transfer(ctx: Context<ProposeVaultOwner>) -> Result<()> {
+        instructions::cancel_vault_owner_transfer(ctx)
+    }
+
+    /// Accepts vault ownership (step two of ownership transfer)
+    pub fn accept_vault_owner(ctx: Context<AcceptVaultOwner>) -> Result<()> {
+        instructions::accept_vault_owner(ctx)
+    }
+
+    /// Migrates a vault and profile to a new agent key
+    pub fn migrate_agent_key(ctx: Context<MigrateAgentKey>) -> Result<()> {
+        instructions::migrate_agent_key(ctx)
+    }
+
+    /// Upgrades a vault created before layout versioning to the current layout
+    pub fn migrate_vault_layout(ctx: Context<MigrateVaultLayout>) -> Result<()> {
+        instructions::migrate_vault_layout(ctx)
+    }
+
+    /// Upgrades a memory shard written before group keys to the current layout
+    pub fn migrate_memory_layout(ctx: Context<MigrateMemoryLayout>) -> Result<()> {
+        instructions::migrate_memory_layout(ctx)
+    }
+
+    /// Upgrades an agent profile written before agent key migration to the current layout
+    pub fn migrate_profile_layout(ctx: Context<MigrateProfileLayout>) -> Result<()> {
+        instructions::migrate_profile_layout(ctx)
+    }
+
+    /// Upgrades an access grant written before permission bits to the current layout
+    pub fn migrate_grant_layout(ctx: Context<MigrateGrantLayout>) -> Result<()> {
+        instructions::migrate_grant_layout(ctx)
+    }
+
+    /// Upgrades a sharing group written with an inline member list to the current layout
+    pub fn migrate_group_layout(ctx: Context<MigrateGroupLayout>) -> Result<()> {
+        instructions::migrate_group_layout(ctx)
+    }
+
+    /// Enables or disables importance-based eviction for over-quota writes
+    pub fn set_eviction_policy(
+        ctx: Context<SetVaultQuota>,
+        enabled: bool,
+    ) -> Result<()> {
+        instructions::set_eviction_policy(ctx, enabled)
+    }
+
+    /// Tallies a chunk of live memory shards towards a vault reconciliation
+    pub fn reconcile_vault(
+        ctx: Context<ReconcileVault>,
+        restart: bool,
+    ) -> Result<()> {
+        instructions::reconcile_vault(ctx, restart)
+    }
+
+    /// Replaces vault counters with the reconciled tallies
+    pub fn finalize_reconcile(ctx: Context<FinalizeReconcile>) -> Result<()> {
+        instructions::finalize_reconcile(ctx)
+    }
+
+    /// Authorizes a scoped delegate to write memories
+    pub fn authorize_delegate(
+        ctx: Context<AuthorizeDelegate>,
+        allowed_memory_types: u8,
+        max_bytes_per_day: u64,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        instructions::authorize_delegate(ctx, allowed_memory_types, max_bytes_per_day, expires_at)
+    }
+
+    /// Sets owner-configured storage quotas on a vault
+    pub fn set_vault_quota(
+        ctx: Context<SetVaultQuota>,
+        max_memory_count: u32,
+        max_total_bytes: u64,
+    ) -> Result<()> {
+        instructions::set_vault_quota(ctx, max_memory_count, max_total_bytes)
+    }
+
+    /// Revokes a delegate and reclaims its account rent
+    pub fn revoke_delegate(ctx: Context<RevokeDelegate>) -> Result<()> {
+        instructions::revoke_delegate(ctx)
+    }
+
     /// Creates a new encrypted memory shard
     pub fn create_memory(
         ctx: Context<CreateMemory>,
@@ -94,8 +210,20 @@ pub mod agent_memory {
         ctx: Context<GrantAccess>,
         permission_level: PermissionLevel,
         expires_at: Option<i64>,
+        scope: Option<GrantScope>,
+        permissions: Option<u8>,
+        not_before: Option<i64>,
+        max_uses: Option<u32>,
     ) -> Result<()> {
-        instructions::grant_access(ctx, permission_level, expires_at)
+        instructions::grant_access(
+            ctx,
+            permission_level,
+            expires_at,
+            scope,
+            permissions,
+            not_before,
+            max_uses,
+        )
     }
 
     /// Revokes access to a vault
@@ -103,6 +231,57 @@ pub mod agent_memory {
         instructions::revoke_access(ctx)
     }
 
+    /// Changes, extends or reactivates an existing access grant
+    pub fn update_access(
+        ctx: Context<UpdateAccess>,
+        permission_level: PermissionLevel,
+        expires_at: Option<i64>,
+        scope: Option<GrantScope>,
+        permissions: Option<u8>,
+        not_before: Option<i64>,
+        max_uses: Option<u32>,
+    ) -> Result<()> {
+        instructions::update_access(
+            ctx,
+            permission_level,
+            expires_at,
+            scope,
+            permissions,
+            not_before,
+            max_uses,
+        )
+    }
+
+    /// Spends one use of a grant to read a memory shard
+    pub fn record_memory_read(ctx: Context<RecordMemoryRead>) -> Result<()> {
+        instructions::record_memory_read(ctx)
+    }
+
+    /// Closes a revoked or expired access grant
+    pub fn close_access_grant(ctx: Context<CloseAccessGrant>) -> Result<()> {
+        instructions::close_access_grant(ctx)
+    }
+
+    /// Closes an expired access grant; callable by anyone
+    pub fn close_expired_access_grant(ctx: Context<CloseExpiredAccessGrant>) -> Result<()> {
+        instructions::close_expired_access_grant(ctx)
+    }
+
+    /// Shares a single memory shard with a grantee
+    pub fn set_memory_acl(
+        ctx: Context<SetMemoryAcl>,
+        permission_level: PermissionLevel,
+        expires_at: Option<i64>,
+        permissions: Option<u8>,
+    ) -> Result<()> {
+        instructions::set_memory_acl(ctx, permission_level, expires_at, permissions)
+    }
+
+    /// Removes a shard-level ACL entry
+    pub fn remove_memory_acl(ctx: Context<RemoveMemoryAcl>) -> Result<()> {
+        instructions::remove_memory_acl(ctx)
+    }
+
     /// Updates agent profile
     pub fn update_profile(
         ctx: Context<UpdateProfile>,
@@ -127,6 +306,63 @@ pub mod agent_memory {
         instructions::create_sharing_group(ctx, name, description)
     }
 
+    /// Adds a member to a sharing group
+    pub fn add_group_member(
+        ctx: Context<AddGroupMember>,
+        member: Pubkey,
+        permission: PermissionLevel,
+        permissions: Option<u8>,
+    ) -> Result<()> {
+        instructions::add_group_member(ctx, member, permission, permissions)
+    }
+
+    /// Removes a member from a sharing group
+    pub fn remove_group_member(ctx: Context<RemoveGroupMember>) -> Result<()> {
+        instructions::remove_group_member(ctx)
+    }
+
+    /// Leaves a sharing group
+    pub fn leave_group(ctx: Context<LeaveGroup>) -> Result<()> {
+        instructions::leave_group(ctx)
+    }
+
+    /// Updates a sharing group's name and description
+    pub fn update_sharing_group(
+        ctx: Context<ManageSharingGroup>,
+        name: Option<String>,
+        description: Option<String>,
+    ) -> Result<()> {
+        instructions::update_sharing_group(ctx, name, description)
+    }
+
+    /// Activates or archives a sharing group
+    pub fn set_group_active(ctx: Context<ManageSharingGroup>, is_active: bool) -> Result<()> {
+        instructions::set_group_active(ctx, is_active)
+    }
+
+    /// Proposes a new sharing group creator
+    pub fn transfer_group_creator(
+        ctx: Context<ManageSharingGroup>,
+        new_creator: Pubkey,
+    ) -> Result<()> {
+        instructions::transfer_group_creator(ctx, new_creator)
+    }
+
+    /// Cancels a pending sharing group creator transfer
+    pub fn cancel_group_creator_transfer(ctx: Context<ManageSharingGroup>) -> Result<()> {
+        instructions::cancel_group_creator_transfer(ctx)
+    }
+
+    /// Accepts a pending sharing group creator transfer
+    pub fn accept_group_creator(ctx: Context<AcceptGroupCreator>) -> Result<()> {
+        instructions::accept_group_creator(ctx)
+    }
+
+    /// Closes an empty sharing group
+    pub fn close_sharing_group(ctx: Context<CloseSharingGroup>) -> Result<()> {
+        instructions::close_sharing_group(ctx)
+    }
+
     /// Initializes protocol configuration
     pub fn initialize_protocol_config(
         ctx: Context<InitializeProtocolConfig>,
@@ -159,6 +395,157 @@ pub mod agent_memory {
         instructions::transfer_admin(ctx, new_admin)
     }
 
+    // ============================================================================
+    // VAULT SNAPSHOT INSTRUCTIONS
+    // ============================================================================
+
+    /// Creates a labelled snapshot of a vault's memory state
+    pub fn create_vault_snapshot(
+        ctx: Context<CreateVaultSnapshot>,
+        label: String,
+        entries: Vec<SnapshotEntry>,
+    ) -> Result<()> {
+        snapshot::create_vault_snapshot(ctx, label, entries)
+    }
+
+    /// Appends shard entries to a snapshot digest
+    pub fn extend_vault_snapshot(
+        ctx: Context<ExtendVaultSnapshot>,
+        entries: Vec<SnapshotEntry>,
+    ) -> Result<()> {
+        snapshot::extend_vault_snapshot(ctx, entries)
+    }
+
+    /// Seals a snapshot so no further entries can be appended
+    pub fn seal_vault_snapshot(ctx: Context<ExtendVaultSnapshot>) -> Result<()> {
+        snapshot::seal_vault_snapshot(ctx)
+    }
+
+    /// Closes a snapshot and returns its rent to the owner
+    pub fn close_vault_snapshot(ctx: Context<CloseVaultSnapshot>) -> Result<()> {
+        snapshot::close_vault_snapshot(ctx)
+    }
+
+    // ============================================================================
+    // VAULT MULTISIG INSTRUCTIONS
+    // ============================================================================
+
+    /// Configures the vault approver set for destructive operations
+    pub fn set_vault_approvers(
+        ctx: Context<SetVaultApprovers>,
+        approvers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        multisig::set_vault_approvers(ctx, approvers, threshold)
+    }
+
+    /// Proposes a multisig-gated vault action
+    pub fn propose_vault_action(
+        ctx: Context<ProposeVaultAction>,
+        action: ProposalAction,
+        expires_at: i64,
+    ) -> Result<()> {
+        multisig::propose_vault_action(ctx, action, expires_at)
+    }
+
+    /// Approves a pending proposal
+    pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+        multisig::approve_proposal(ctx)
+    }
+
+    /// Closes an executed or expired proposal
+    pub fn expire_proposal(ctx: Context<ExpireProposal>) -> Result<()> {
+        multisig::expire_proposal(ctx)
+    }
+
+    // ============================================================================
+    // SOCIAL RECOVERY INSTRUCTIONS
+    // ============================================================================
+
+    /// Configures recovery guardians, threshold and veto delay
+    pub fn set_recovery_guardians(
+        ctx: Context<SetRecoveryGuardians>,
+        guardians: Vec<Pubkey>,
+        threshold: u8,
+        delay: i64,
+    ) -> Result<()> {
+        recovery::set_recovery_guardians(ctx, guardians, threshold, delay)
+    }
+
+    /// Opens a guardian recovery request naming a new owner
+    pub fn initiate_recovery(ctx: Context<InitiateRecovery>, new_owner: Pubkey) -> Result<()> {
+        recovery::initiate_recovery(ctx, new_owner)
+    }
+
+    /// Approves a pending recovery request
+    pub fn approve_recovery(ctx: Context<ApproveRecovery>) -> Result<()> {
+        recovery::approve_recovery(ctx)
+    }
+
+    /// Hands the vault to the recovered owner after the veto delay
+    pub fn finalize_recovery(ctx: Context<FinalizeRecovery>) -> Result<()> {
+        recovery::finalize_recovery(ctx)
+    }
+
+    /// Owner veto of a pending recovery request
+    pub fn cancel_recovery(ctx: Context<CancelRecovery>) -> Result<()> {
+        recovery::cancel_recovery(ctx)
+    }
+
+    /// Closes an expired or superseded recovery request
+    pub fn expire_recovery(ctx: Context<ExpireRecovery>) -> Result<()> {
+        recovery::expire_recovery(ctx)
+    }
+
+    // ============================================================================
+    // INHERITANCE INSTRUCTIONS
+    // ============================================================================
+
+    /// Configures the beneficiary and inactivity period of the dead-man switch
+    pub fn set_inheritance(
+        ctx: Context<SetInheritance>,
+        beneficiary: Option<Pubkey>,
+        inactivity_period: i64,
+        mode: InheritanceMode,
+    ) -> Result<()> {
+        inheritance::set_inheritance(ctx, beneficiary, inactivity_period, mode)
+    }
+
+    /// Records owner activity without changing vault state
+    pub fn heartbeat(ctx: Context<Heartbeat>) -> Result<()> {
+        inheritance::heartbeat(ctx)
+    }
+
+    /// Passes the vault to its beneficiary after the inactivity period
+    pub fn claim_inheritance(ctx: Context<ClaimInheritance>) -> Result<()> {
+        inheritance::claim_inheritance(ctx)
+    }
+
+    // ============================================================================
+    // GROUP KEY INSTRUCTIONS
+    // ============================================================================
+
+    /// Starts a new group key epoch with a first batch of wrapped member keys
+    pub fn rotate_group_key(
+        ctx: Context<RotateGroupKey>,
+        wrapped_keys: Vec<WrappedGroupKey>,
+    ) -> Result<()> {
+        group_keys::rotate_group_key(ctx, wrapped_keys)
+    }
+
+    /// Adds wrapped member keys to the current group key epoch
+    pub fn add_wrapped_group_keys(
+        ctx: Context<AddWrappedGroupKeys>,
+        wrapped_keys: Vec<WrappedGroupKey>,
+    ) -> Result<()> {
+        group_keys::add_wrapped_group_keys(ctx, wrapped_keys)
+    }
+
+    /// Closes a superseded group key epoch, or any epoch of an archived group
+    pub fn close_group_key_epoch(ctx: Context<CloseGroupKeyEpoch>) -> Result<()> {
+        group_keys::close_group_key_epoch(ctx)
+    }
+
     // ============================================================================
     // IDENTITY BINDING INSTRUCTIONS (ERC-8004)
     // ============================================================================
@@ -215,6 +602,13 @@ pub mod agent_memory {
 // ============================================================================
 
 pub const MAX_KEY_LENGTH: usize = 64;
+
+/// Current `MemoryVault` layout; vaults without the field are layout 0
+pub const VAULT_LAYOUT_VERSION: u8 = 1;
+
+/// Maximum number of required tags on a grant scope
+pub const MAX_SCOPE_TAGS: usize = 8;
+pub const MAX_GRANT_DEPTH: u8 = 4;
 pub const MAX_NAME_LENGTH: usize = 128;
 pub const MAX_CAPABILITIES: usize = 20;
 pub const MAX_CAPABILITY_LENGTH: usize = 64;
@@ -222,10 +616,21 @@ pub const MAX_CONTENT_SIZE: u32 = 10_000_000; // 10MB
 pub const MAX_BATCH_SIZE: usize = 10;
 pub const MAX_GROUP_NAME_LENGTH: usize = 64;
 pub const MAX_GROUP_DESC_LENGTH: usize = 256;
-pub const MAX_GROUP_MEMBERS: usize = 50;
 pub const REPUTATION_PER_TASK: u32 = 10;
 pub const MAX_REPUTATION: u32 = 10000;
 pub const TASK_RATE_LIMIT_SECONDS: i64 = 60; // 1 minute between tasks
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+pub const QUOTA_THRESHOLDS_PERCENT: [u8; 2] = [80, 100];
+
+// Permission bits carried by grants, ACL entries and group members
+pub const PERMISSION_READ: u8 = 1 << 0;
+pub const PERMISSION_CREATE: u8 = 1 << 1;
+pub const PERMISSION_UPDATE: u8 = 1 << 2;
+pub const PERMISSION_DELETE: u8 = 1 << 3;
+pub const PERMISSION_RESTORE: u8 = 1 << 4;
+pub const PERMISSION_SHARE: u8 = 1 << 5;
+pub const PERMISSION_GRANT: u8 = 1 << 6;
+pub const PERMISSION_ALL: u8 = (1 << 7) - 1;
 
 // ============================================================================
 // EVENTS
@@ -240,6 +645,156 @@ pub struct VaultInitialized {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct VaultClosed {
+    pub vault: Pubkey,
+    pub profile: Pubkey,
+    pub owner: Pubkey,
+    pub agent_key: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultActiveChanged {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub is_active: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultOwnerProposed {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub pending_owner: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultOwnerTransferred {
+    pub vault: Pubkey,
+    pub profile: Pubkey,
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultLayoutMigrated {
+    pub vault: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MemoryLayoutMigrated {
+    pub vault: Pubkey,
+    pub memory: Pubkey,
+    pub is_deleted: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProfileLayoutMigrated {
+    pub vault: Pubkey,
+    pub profile: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GrantLayoutMigrated {
+    pub vault: Pubkey,
+    pub grant: Pubkey,
+    pub grantee: Pubkey,
+    pub is_active: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GroupLayoutMigrated {
+    pub vault: Pubkey,
+    pub group: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgentKeyMigrated {
+    pub owner: Pubkey,
+    pub old_vault: Pubkey,
+    pub new_vault: Pubkey,
+    pub old_profile: Pubkey,
+    pub new_profile: Pubkey,
+    pub old_agent_key: Pubkey,
+    pub new_agent_key: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelegateAuthorized {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub allowed_memory_types: u8,
+    pub max_bytes_per_day: u64,
+    pub expires_at: Option<i64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelegateRevoked {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultQuotaUpdated {
+    pub vault: Pubkey,
+    pub max_memory_count: u32,
+    pub max_total_bytes: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct QuotaThresholdReached {
+    pub vault: Pubkey,
+    pub threshold_percent: u8,
+    pub memory_count: u32,
+    pub max_memory_count: u32,
+    pub total_memory_size: u64,
+    pub max_total_bytes: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EvictionPolicyChanged {
+    pub vault: Pubkey,
+    pub enabled: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MemoryEvicted {
+    pub vault: Pubkey,
+    pub memory: Pubkey,
+    pub key: String,
+    pub importance: u8,
+    pub content_size: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultReconciled {
+    pub vault: Pubkey,
+    pub old_memory_count: u32,
+    pub new_memory_count: u32,
+    pub old_total_memory_size: u64,
+    pub new_total_memory_size: u64,
+    pub old_deleted_memory_count: u32,
+    pub new_deleted_memory_count: u32,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct MemoryCreated {
     pub vault: Pubkey,
@@ -337,8 +892,13 @@ pub struct AccessGranted {
     pub grantee: Pubkey,
     pub granted_by: Pubkey,
     pub permission_level: u8,
+    pub permissions: u8,
     pub granted_at: i64,
     pub expires_at: Option<i64>,
+    pub not_before: Option<i64>,
+    pub max_uses: Option<u32>,
+    pub scope: GrantScope,
+    pub parent_grant: Option<Pubkey>,
 }
 
 #[event]
@@ -350,11 +910,61 @@ pub struct AccessRevoked {
 }
 
 #[event]
-pub struct SharingGroupCreated {
-    pub group: Pubkey,
-    pub creator: Pubkey,
+pub struct AccessUpdated {
     pub vault: Pubkey,
-    pub name: String,
+    pub grantee: Pubkey,
+    pub updated_by: Pubkey,
+    pub old_permission_level: u8,
+    pub new_permission_level: u8,
+    pub old_permissions: u8,
+    pub new_permissions: u8,
+    pub old_expires_at: Option<i64>,
+    pub new_expires_at: Option<i64>,
+    pub old_not_before: Option<i64>,
+    pub new_not_before: Option<i64>,
+    pub old_remaining_uses: Option<u32>,
+    pub new_remaining_uses: Option<u32>,
+    pub old_scope: GrantScope,
+    pub new_scope: GrantScope,
+    pub reactivated: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccessExhausted {
+    pub vault: Pubkey,
+    pub grantee: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccessGrantClosed {
+    pub vault: Pubkey,
+    pub grantee: Pubkey,
+    pub closed_by: Pubkey,
+    pub payer: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MemoryAclUpdated {
+    pub vault: Pubkey,
+    pub memory: Pubkey,
+    pub grantee: Pubkey,
+    pub updated_by: Pubkey,
+    /// `None` when the entry was removed
+    pub permission_level: Option<u8>,
+    pub permissions: u8,
+    pub expires_at: Option<i64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SharingGroupCreated {
+    pub group: Pubkey,
+    pub creator: Pubkey,
+    pub vault: Pubkey,
+    pub name: String,
     pub timestamp: i64,
 }
 
@@ -363,6 +973,7 @@ pub struct GroupMemberAdded {
     pub group: Pubkey,
     pub member: Pubkey,
     pub permission: u8,
+    pub permissions: u8,
     pub timestamp: i64,
 }
 
@@ -373,6 +984,44 @@ pub struct GroupMemberRemoved {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct SharingGroupUpdated {
+    pub group: Pubkey,
+    pub name: String,
+    pub description: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SharingGroupStatusChanged {
+    pub group: Pubkey,
+    pub is_active: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GroupCreatorProposed {
+    pub group: Pubkey,
+    pub creator: Pubkey,
+    pub pending_creator: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GroupCreatorTransferred {
+    pub group: Pubkey,
+    pub old_creator: Pubkey,
+    pub new_creator: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SharingGroupClosed {
+    pub group: Pubkey,
+    pub creator: Pubkey,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct MemoryAccessLogged {
     pub memory: Pubkey,
@@ -446,6 +1095,101 @@ pub struct AdminTransferred {
 #[account]
 #[derive(InitSpace)]
 pub struct MemoryVault {
+    pub owner: Pubkey,
+    /// Account layout version (`VAULT_LAYOUT_VERSION` when written by this program)
+    pub layout_version: u8,
+    /// Owner the vault PDA was derived from; fixed across ownership transfers
+    pub seed_owner: Pubkey,
+    pub pending_owner: Option<Pubkey>,
+    /// Bumped on every ownership change so grants, ACL entries, delegates and
+    /// groups issued under a previous owner stop working
+    pub owner_epoch: u32,
+    pub agent_key: Pubkey,
+    pub encryption_pubkey: [u8; 32],
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub memory_count: u32,
+    pub total_memory_size: u64,
+    /// Soft-deleted shards still holding rent; not counted in `memory_count`
+    pub deleted_memory_count: u32,
+    /// Shard accounts ever created or migrated; never decremented
+    pub shards_created: u64,
+    /// Shard accounts ever permanently deleted; never decremented
+    pub shards_closed: u64,
+    pub staked_amount: u64,
+    pub reward_points: u32,
+    pub active_grant_count: u32,
+    /// Delegates, grants, ACL entries, groups, snapshots, proposals and recovery
+    /// requests not yet closed; `close_vault` waits for all of them
+    pub open_account_count: u32,
+    /// Owner-set shard limit (0 = unlimited)
+    pub max_memory_count: u32,
+    /// Owner-set storage limit in bytes (0 = unlimited)
+    pub max_total_bytes: u64,
+    /// Whether over-quota writes may evict caller-supplied shards
+    pub eviction_enabled: bool,
+    /// Keys allowed to approve destructive operations
+    #[max_len(MAX_APPROVERS)]
+    pub approvers: Vec<Pubkey>,
+    /// Approvals required for destructive operations (0 = multisig disabled)
+    pub approval_threshold: u8,
+    pub proposal_count: u64,
+    /// Keys allowed to recover ownership if the owner key is lost
+    #[max_len(MAX_GUARDIANS)]
+    pub guardians: Vec<Pubkey>,
+    /// Guardian approvals required for recovery (0 = recovery disabled)
+    pub guardian_threshold: u8,
+    /// Seconds the owner has to veto a recovery request once guardians approve it
+    pub recovery_delay: i64,
+    /// Receives the vault after owner inactivity (None = switch disarmed)
+    pub beneficiary: Option<Pubkey>,
+    /// Seconds without owner activity before inheritance can be claimed
+    pub inactivity_period: i64,
+    pub inheritance_mode: InheritanceMode,
+    /// Last owner-signed activity on the vault
+    pub last_heartbeat: i64,
+    /// Address memory shards are derived from; carried over on agent key migration
+    pub memory_root: Pubkey,
+    /// Successor vault once the agent key has been migrated
+    pub migrated_to: Option<Pubkey>,
+    pub is_active: bool,
+    pub bump: u8,
+}
+
+impl MemoryVault {
+    /// Resets the inactivity timer if `signer` is the owner
+    pub fn record_heartbeat(&mut self, signer: &Pubkey, now: i64) {
+        if *signer == self.owner {
+            self.last_heartbeat = now;
+        }
+    }
+
+    /// Whether live usage is above either owner-set quota
+    pub fn exceeds_quota(&self) -> bool {
+        (self.max_memory_count != 0 && self.memory_count > self.max_memory_count)
+            || (self.max_total_bytes != 0 && self.total_memory_size > self.max_total_bytes)
+    }
+
+    /// Counts a newly created account derived from this vault
+    pub fn track_open_account(&mut self) -> Result<()> {
+        self.open_account_count = self
+            .open_account_count
+            .checked_add(1)
+            .ok_or(AgentMemoryError::Overflow)?;
+        Ok(())
+    }
+
+    /// Releases an account counted by `track_open_account` once it is closed
+    pub fn release_open_account(&mut self) {
+        self.open_account_count = self.open_account_count.saturating_sub(1);
+    }
+}
+
+/// `MemoryVault` layout 0, as written before layout versioning
+/// 
+/// Only read by `migrate_vault_layout`.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct LegacyMemoryVault {
     pub owner: Pubkey,
     pub agent_key: Pubkey,
     pub encryption_pubkey: [u8; 32],
@@ -463,6 +1207,28 @@ pub struct MemoryVault {
 #[account]
 #[derive(InitSpace)]
 pub struct MemoryShard {
+    pub vault: Pubkey,
+    #[max_len(MAX_KEY_LENGTH)]
+    pub key: String,
+    pub content_hash: [u8; 32],
+    pub content_size: u32,
+    pub metadata: MemoryMetadata,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub version: u32,
+    pub is_deleted: bool,
+    pub deleted_at: Option<i64>,
+    pub previous_version_hash: Option<[u8; 32]>,
+    /// `GroupKeyEpoch` the content is encrypted under; `None` for the vault key
+    pub group_key_epoch: Option<Pubkey>,
+    pub bump: u8,
+}
+
+/// `MemoryShard` layout written before group keys
+/// 
+/// Only read by `migrate_memory_layout`.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct LegacyMemoryShard {
     pub vault: Pubkey,
     #[max_len(MAX_KEY_LENGTH)]
     pub key: String,
@@ -478,6 +1244,38 @@ pub struct MemoryShard {
     pub bump: u8,
 }
 
+/// Running tally of a vault's live shards, built up over `reconcile_vault` calls
+#[account]
+#[derive(InitSpace)]
+pub struct VaultReconciliation {
+    pub vault: Pubkey,
+    /// Live shards tallied so far
+    pub memory_count: u32,
+    /// Content bytes of the live shards tallied so far
+    pub total_memory_size: u64,
+    /// Soft-deleted shards tallied so far
+    pub deleted_memory_count: u32,
+    /// Highest shard address tallied; shards must be supplied in ascending order
+    pub last_shard: Pubkey,
+    /// Vault `updated_at` when the tally started; any later write makes it stale
+    pub vault_updated_at: i64,
+    /// Vault `memory_count` when the tally started
+    pub vault_memory_count: u32,
+    /// Vault `total_memory_size` when the tally started
+    pub vault_total_memory_size: u64,
+    pub started_at: i64,
+    pub bump: u8,
+}
+
+impl VaultReconciliation {
+    /// Whether the vault has been written to since the tally started
+    pub fn is_stale(&self, vault: &MemoryVault) -> bool {
+        vault.updated_at != self.vault_updated_at
+            || vault.memory_count != self.vault_memory_count
+            || vault.total_memory_size != self.vault_total_memory_size
+    }
+}
+
 /// Agent profile - public information
 #[account]
 #[derive(InitSpace)]
@@ -495,6 +1293,48 @@ pub struct AgentProfile {
     pub updated_at: i64,
     pub last_task_at: i64,
     pub is_public: bool,
+    /// Successor profile once the agent key has been migrated
+    pub migrated_to: Option<Pubkey>,
+    pub bump: u8,
+}
+
+/// `AgentProfile` layout written before agent key migration
+/// 
+/// Only read by `migrate_profile_layout`.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct LegacyAgentProfile {
+    pub agent_key: Pubkey,
+    pub owner: Pubkey,
+    pub vault: Pubkey,
+    #[max_len(MAX_NAME_LENGTH)]
+    pub name: String,
+    #[max_len(MAX_CAPABILITIES, MAX_CAPABILITY_LENGTH)]
+    pub capabilities: Vec<String>,
+    pub reputation_score: u32,
+    pub tasks_completed: u32,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub last_task_at: i64,
+    pub is_public: bool,
+    pub bump: u8,
+}
+
+/// Session key allowed to write memories on the owner's behalf
+#[account]
+#[derive(InitSpace)]
+pub struct SessionDelegate {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+    /// Bitmask over `MemoryType` discriminants (0 = all types)
+    pub allowed_memory_types: u8,
+    /// Daily write allowance in bytes (0 = unlimited)
+    pub max_bytes_per_day: u64,
+    pub bytes_written_today: u64,
+    pub day_started_at: i64,
+    pub expires_at: Option<i64>,
+    pub created_at: i64,
+    /// `MemoryVault::owner_epoch` when issued; stops working once ownership changes
+    pub owner_epoch: u32,
     pub bump: u8,
 }
 
@@ -502,6 +1342,39 @@ pub struct AgentProfile {
 #[account]
 #[derive(InitSpace)]
 pub struct AccessGrant {
+    pub vault: Pubkey,
+    pub grantee: Pubkey,
+    pub permission_level: PermissionLevel,
+    /// `PERMISSION_*` bits actually enforced
+    pub permissions: u8,
+    pub granted_at: i64,
+    pub expires_at: Option<i64>,
+    /// Grant is unusable before this time (`None` = immediately)
+    pub not_before: Option<i64>,
+    /// Actions left before the grant deactivates (`None` = unlimited)
+    pub remaining_uses: Option<u32>,
+    pub is_active: bool,
+    pub revoked_at: Option<i64>,
+    /// Account that paid the grant's rent and receives it back on close
+    pub payer: Pubkey,
+    /// Shards the grant applies to (default = whole vault)
+    pub scope: GrantScope,
+    /// Grant this one was issued under (`None` = issued by the vault owner)
+    pub parent_grant: Option<Pubkey>,
+    /// `granted_at` of the parent, so a re-issued parent doesn't revive children
+    pub parent_granted_at: i64,
+    /// Number of ancestor grants
+    pub depth: u8,
+    /// `MemoryVault::owner_epoch` when issued; stops working once ownership changes
+    pub owner_epoch: u32,
+    pub bump: u8,
+}
+
+/// `AccessGrant` layout written before permission bits
+/// 
+/// Only read by `migrate_grant_layout`.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct LegacyAccessGrant {
     pub vault: Pubkey,
     pub grantee: Pubkey,
     pub permission_level: PermissionLevel,
@@ -512,18 +1385,112 @@ pub struct AccessGrant {
     pub bump: u8,
 }
 
+/// Shard-level access control entry
+/// 
+/// Checked before the grantee's vault-level `AccessGrant`.
+#[account]
+#[derive(InitSpace)]
+pub struct MemoryAcl {
+    pub memory: Pubkey,
+    pub vault: Pubkey,
+    pub grantee: Pubkey,
+    pub permission_level: PermissionLevel,
+    /// `PERMISSION_*` bits actually enforced
+    pub permissions: u8,
+    /// Creation time of the shard, so entries don't carry over to a recreated shard
+    pub memory_created_at: i64,
+    pub granted_by: Pubkey,
+    pub granted_at: i64,
+    pub expires_at: Option<i64>,
+    /// Grant the entry was issued under (`None` = issued by the vault owner)
+    pub issuer_grant: Option<Pubkey>,
+    /// `granted_at` of the issuer grant, so a re-issued grant doesn't revive the entry
+    pub issuer_granted_at: i64,
+    /// Length of the issuer's grant chain (issuer grant plus its ancestors)
+    pub issuer_depth: u8,
+    /// `MemoryVault::owner_epoch` when issued; stops working once ownership changes
+    pub owner_epoch: u32,
+    /// Account that paid the entry's rent and receives it back on removal
+    pub payer: Pubkey,
+    pub bump: u8,
+}
+
+/// Restricts an access grant to a slice of the vault
+/// 
+/// A shard is in scope only if it matches every non-empty restriction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace, PartialEq)]
+pub struct GrantScope {
+    /// Required key prefix (empty = any key)
+    #[max_len(MAX_KEY_LENGTH)]
+    pub key_prefix: String,
+    /// Bitmask over `MemoryType` discriminants (0 = all types)
+    pub memory_types: u8,
+    /// Tag ids the shard must carry
+    #[max_len(MAX_SCOPE_TAGS)]
+    pub required_tags: Vec<u8>,
+}
+
+impl GrantScope {
+    /// Whether a shard with `key` and `metadata` falls within this scope
+    pub fn allows(&self, key: &str, metadata: &MemoryMetadata) -> bool {
+        key.starts_with(self.key_prefix.as_str())
+            && (self.memory_types == 0 || self.memory_types & metadata.memory_type.mask() != 0)
+            && self.required_tags.iter().all(|tag| metadata.tags.contains(tag))
+    }
+    
+    /// Whether every shard this scope allows is also allowed by `parent`
+    pub fn is_within(&self, parent: &GrantScope) -> bool {
+        self.key_prefix.starts_with(parent.key_prefix.as_str())
+            && (parent.memory_types == 0
+                || (self.memory_types != 0 && self.memory_types & !parent.memory_types == 0))
+            && parent.required_tags.iter().all(|tag| self.required_tags.contains(tag))
+    }
+}
+
 /// Sharing group for collaborative memory access
 #[account]
 #[derive(InitSpace)]
 pub struct SharingGroup {
+    pub creator: Pubkey,
+    pub pending_creator: Option<Pubkey>,
+    pub vault: Pubkey,
+    /// Name the group PDA was derived from; fixed across renames
+    #[max_len(MAX_GROUP_NAME_LENGTH)]
+    pub seed_name: String,
+    #[max_len(MAX_GROUP_NAME_LENGTH)]
+    pub name: String,
+    #[max_len(MAX_GROUP_DESC_LENGTH)]
+    pub description: String,
+    /// Number of `GroupMember` records
+    pub member_count: u32,
+    /// Current `GroupKeyEpoch` (0 = no group key yet)
+    pub key_epoch: u32,
+    /// Number of `GroupKeyEpoch` accounts not yet closed
+    pub open_key_epochs: u32,
+    /// Set when a member leaves; member writes and the current key are rejected until rotation
+    pub key_rotation_pending: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub is_active: bool,
+    /// `MemoryVault::owner_epoch` when created or last activated; memberships
+    /// stop working once ownership changes
+    pub owner_epoch: u32,
+    pub bump: u8,
+}
+
+/// `SharingGroup` layout written with an inline member list
+/// 
+/// Only read by `migrate_group_layout`.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct LegacySharingGroup {
     pub creator: Pubkey,
     pub vault: Pubkey,
     #[max_len(MAX_GROUP_NAME_LENGTH)]
     pub name: String,
     #[max_len(MAX_GROUP_DESC_LENGTH)]
     pub description: String,
-    #[max_len(MAX_GROUP_MEMBERS)]
-    pub members: Vec<GroupMember>,
+    #[max_len(50)]
+    pub members: Vec<LegacyGroupMember>,
     pub member_count: u32,
     pub created_at: i64,
     pub updated_at: i64,
@@ -531,12 +1498,27 @@ pub struct SharingGroup {
     pub bump: u8,
 }
 
-/// Group member entry
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+/// Inline member entry of `LegacySharingGroup`
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct LegacyGroupMember {
+    pub member: Pubkey,
+    pub permission: PermissionLevel,
+    pub joined_at: i64,
+}
+
+/// Sharing group membership record, one PDA per (group, member)
+#[account]
+#[derive(InitSpace)]
 pub struct GroupMember {
+    pub group: Pubkey,
     pub member: Pubkey,
     pub permission: PermissionLevel,
+    /// `PERMISSION_*` bits actually enforced
+    pub permissions: u8,
     pub joined_at: i64,
+    /// Account that paid the record's rent and receives it back on removal
+    pub payer: Pubkey,
+    pub bump: u8,
 }
 
 /// Access log entry
@@ -594,6 +1576,13 @@ impl Default for MemoryType {
     }
 }
 
+impl MemoryType {
+    /// Bit for this type in a memory type bitmask
+    pub fn mask(&self) -> u8 {
+        1 << (self.clone() as u8)
+    }
+}
+
 /// Permission levels for access control
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace, PartialEq)]
 pub enum PermissionLevel {
@@ -609,6 +1598,24 @@ impl Default for PermissionLevel {
     }
 }
 
+impl PermissionLevel {
+    /// Permission bits equivalent to this level
+    pub fn permissions(&self) -> u8 {
+        match self {
+            PermissionLevel::None => 0,
+            PermissionLevel::Read => PERMISSION_READ,
+            PermissionLevel::Write => {
+                PERMISSION_READ
+                    | PERMISSION_CREATE
+                    | PERMISSION_UPDATE
+                    | PERMISSION_DELETE
+                    | PERMISSION_RESTORE
+            }
+            PermissionLevel::Admin => PERMISSION_ALL,
+        }
+    }
+}
+
 /// Access types for logging
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
 pub enum AccessType {
@@ -693,140 +1700,971 @@ pub struct InitializeVault<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(key: String)]
-pub struct CreateMemory<'info> {
+pub struct CloseVault<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"profile", vault.agent_key.as_ref()],
+        bump = agent_profile.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+        has_one = vault,
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+}
+
+#[derive(Accounts)]
+pub struct SetVaultActive<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
     
     #[account(
         mut,
-        seeds = [b"vault", vault.owner.as_ref(), vault.agent_key.as_ref()],
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
         bump = vault.bump,
         has_one = owner @ AgentMemoryError::UnauthorizedOwner,
     )]
     pub vault: Account<'info, MemoryVault>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeVaultOwner<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
     
     #[account(
-        init,
-        payer = owner,
-        space = 8 + MemoryShard::INIT_SPACE,
-        seeds = [b"memory", vault.key().as_ref(), key.as_bytes()],
-        bump
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
     )]
-    pub memory_shard: Account<'info, MemoryShard>,
+    pub vault: Account<'info, MemoryVault>,
     
-    /// CHECK: Protocol config for pause state
+    /// Approved proposal, required when the vault has an approver set
     #[account(
-        seeds = [b"config"],
-        bump = protocol_config.bump,
+        mut,
+        seeds = [b"proposal", vault.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
     )]
-    pub protocol_config: Account<'info, ProtocolConfig>,
+    pub proposal: Option<Account<'info, VaultProposal>>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptVaultOwner<'info> {
+    #[account(mut)]
+    pub new_owner: Signer<'info>,
     
-    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        constraint = vault.pending_owner == Some(new_owner.key()) @ AgentMemoryError::NoPendingOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    #[account(
+        mut,
+        seeds = [b"profile", vault.agent_key.as_ref()],
+        bump = agent_profile.bump,
+        has_one = vault,
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateMemory<'info> {
+pub struct MigrateAgentKey<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
     
+    pub new_agent_key: Signer<'info>,
+    
     #[account(
         mut,
-        seeds = [b"vault", vault.owner.as_ref(), vault.agent_key.as_ref()],
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
         bump = vault.bump,
         has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+        constraint = vault.migrated_to.is_none() @ AgentMemoryError::AlreadyMigrated,
     )]
     pub vault: Account<'info, MemoryVault>,
     
     #[account(
         mut,
-        seeds = [b"memory", vault.key().as_ref(), memory_shard.key.as_bytes()],
-        bump = memory_shard.bump,
-        constraint = !memory_shard.is_deleted @ AgentMemoryError::MemoryAlreadyDeleted,
+        seeds = [b"profile", vault.agent_key.as_ref()],
+        bump = agent_profile.bump,
+        has_one = vault,
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + MemoryVault::INIT_SPACE,
+        seeds = [b"vault", owner.key().as_ref(), new_agent_key.key().as_ref()],
+        bump
+    )]
+    pub new_vault: Account<'info, MemoryVault>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + AgentProfile::INIT_SPACE,
+        seeds = [b"profile", new_agent_key.key().as_ref()],
+        bump
+    )]
+    pub new_agent_profile: Account<'info, AgentProfile>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateVaultLayout<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    /// CHECK: Legacy vaults don't deserialize as `MemoryVault`; the program
+    /// owner, discriminator, size and PDA are checked by the instruction
+    #[account(mut)]
+    pub vault: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateMemoryLayout<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    /// CHECK: Legacy shards don't deserialize as `MemoryShard`; the program
+    /// owner, discriminator, size and PDA are checked by the instruction
+    #[account(mut)]
+    pub memory_shard: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateProfileLayout<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    /// CHECK: Legacy profiles don't deserialize as `AgentProfile`; the program
+    /// owner, discriminator, size and PDA are checked by the instruction
+    #[account(mut)]
+    pub agent_profile: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateGrantLayout<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    /// CHECK: Legacy grants don't deserialize as `AccessGrant`; the program
+    /// owner, discriminator, size and PDA are checked by the instruction
+    #[account(mut)]
+    pub access_grant: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateGroupLayout<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    /// CHECK: Legacy groups don't deserialize as `SharingGroup`; the program
+    /// owner, discriminator, size and PDA are checked by the instruction
+    #[account(mut)]
+    pub sharing_group: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetVaultQuota<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+}
+
+#[derive(Accounts)]
+pub struct ReconcileVault<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + VaultReconciliation::INIT_SPACE,
+        seeds = [b"reconciliation", vault.key().as_ref()],
+        bump
+    )]
+    pub reconciliation: Account<'info, VaultReconciliation>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeReconcile<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"reconciliation", vault.key().as_ref()],
+        bump = reconciliation.bump,
+        has_one = vault,
+    )]
+    pub reconciliation: Account<'info, VaultReconciliation>,
+}
+
+#[derive(Accounts)]
+pub struct AuthorizeDelegate<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    /// CHECK: The session key being authorized
+    pub delegate_key: AccountInfo<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + SessionDelegate::INIT_SPACE,
+        seeds = [b"delegate", vault.key().as_ref(), delegate_key.key().as_ref()],
+        bump
+    )]
+    pub delegate: Account<'info, SessionDelegate>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeDelegate<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"delegate", vault.key().as_ref(), delegate.delegate.as_ref()],
+        bump = delegate.bump,
+    )]
+    pub delegate: Account<'info, SessionDelegate>,
+}
+
+#[derive(Accounts)]
+#[instruction(key: String)]
+pub struct CreateMemory<'info> {
+    /// Vault owner, an authorized delegate, a permitted grantee or group member
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MemoryShard::INIT_SPACE,
+        seeds = [b"memory", vault.memory_root.as_ref(), key.as_bytes()],
+        bump
+    )]
+    pub memory_shard: Account<'info, MemoryShard>,
+    
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        mut,
+        seeds = [b"delegate", vault.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump,
+    )]
+    pub delegate: Option<Account<'info, SessionDelegate>>,
+    
+    /// Access grant of `authority` when it is not the owner or a delegate
+    #[account(
+        mut,
+        seeds = [b"access", vault.key().as_ref(), authority.key().as_ref()],
+        bump = access_grant.bump,
+    )]
+    pub access_grant: Option<Account<'info, AccessGrant>>,
+    
+    /// Sharing group of the vault `authority` belongs to
+    #[account(
+        seeds = [b"group", vault.key().as_ref(), sharing_group.seed_name.as_bytes()],
+        bump = sharing_group.bump,
+        has_one = vault,
+    )]
+    pub sharing_group: Option<Account<'info, SharingGroup>>,
+    
+    /// Membership record of `authority` in `sharing_group`
+    #[account(
+        seeds = [b"group_member", group_member.group.as_ref(), authority.key().as_ref()],
+        bump = group_member.bump,
+    )]
+    pub group_member: Option<Account<'info, GroupMember>>,
+    
+    /// Group key the content is encrypted under, if group-encrypted
+    #[account(
+        seeds = [b"group_key", group_key_epoch.group.as_ref(), &group_key_epoch.epoch.to_le_bytes()],
+        bump = group_key_epoch.bump,
+    )]
+    pub group_key_epoch: Option<Account<'info, GroupKeyEpoch>>,
+    
+    /// CHECK: Protocol config for pause state
+    #[account(
+        seeds = [b"config"],
+        bump = protocol_config.bump,
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMemory<'info> {
+    /// Vault owner, an authorized delegate, a permitted grantee or group member
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    #[account(
+        mut,
+        seeds = [b"memory", vault.memory_root.as_ref(), memory_shard.key.as_bytes()],
+        bump = memory_shard.bump,
+        constraint = !memory_shard.is_deleted @ AgentMemoryError::MemoryAlreadyDeleted,
+    )]
+    pub memory_shard: Account<'info, MemoryShard>,
+    
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        mut,
+        seeds = [b"delegate", vault.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump,
+    )]
+    pub delegate: Option<Account<'info, SessionDelegate>>,
+    
+    /// Access grant of `authority` when it is not the owner or a delegate
+    #[account(
+        mut,
+        seeds = [b"access", vault.key().as_ref(), authority.key().as_ref()],
+        bump = access_grant.bump,
+    )]
+    pub access_grant: Option<Account<'info, AccessGrant>>,
+    
+    /// Shard-level ACL of `authority`, checked before `access_grant`
+    #[account(
+        seeds = [b"acl", memory_shard.key().as_ref(), authority.key().as_ref()],
+        bump = memory_acl.bump,
+        constraint = memory_acl.memory_created_at == memory_shard.created_at @ AgentMemoryError::AccessNotGranted,
+    )]
+    pub memory_acl: Option<Account<'info, MemoryAcl>>,
+    
+    /// Sharing group of the vault `authority` belongs to
+    #[account(
+        seeds = [b"group", vault.key().as_ref(), sharing_group.seed_name.as_bytes()],
+        bump = sharing_group.bump,
+        has_one = vault,
+    )]
+    pub sharing_group: Option<Account<'info, SharingGroup>>,
+    
+    /// Membership record of `authority` in `sharing_group`
+    #[account(
+        seeds = [b"group_member", group_member.group.as_ref(), authority.key().as_ref()],
+        bump = group_member.bump,
+    )]
+    pub group_member: Option<Account<'info, GroupMember>>,
+    
+    /// Group key the content is encrypted under, if group-encrypted
+    #[account(
+        seeds = [b"group_key", group_key_epoch.group.as_ref(), &group_key_epoch.epoch.to_le_bytes()],
+        bump = group_key_epoch.bump,
+    )]
+    pub group_key_epoch: Option<Account<'info, GroupKeyEpoch>>,
+    
+    /// CHECK: Protocol config for pause state
+    #[account(
+        seeds = [b"config"],
+        bump = protocol_config.bump,
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct DeleteMemory<'info> {
+    /// Vault owner, an authorized delegate, a permitted grantee or group member
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    #[account(
+        mut,
+        seeds = [b"memory", vault.memory_root.as_ref(), memory_shard.key.as_bytes()],
+        bump = memory_shard.bump,
+        constraint = !memory_shard.is_deleted @ AgentMemoryError::MemoryAlreadyDeleted,
+    )]
+    pub memory_shard: Account<'info, MemoryShard>,
+    
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        mut,
+        seeds = [b"delegate", vault.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump,
+    )]
+    pub delegate: Option<Account<'info, SessionDelegate>>,
+    
+    /// Access grant of `authority` when it is not the owner or a delegate
+    #[account(
+        mut,
+        seeds = [b"access", vault.key().as_ref(), authority.key().as_ref()],
+        bump = access_grant.bump,
+    )]
+    pub access_grant: Option<Account<'info, AccessGrant>>,
+    
+    /// Shard-level ACL of `authority`, checked before `access_grant`
+    #[account(
+        seeds = [b"acl", memory_shard.key().as_ref(), authority.key().as_ref()],
+        bump = memory_acl.bump,
+        constraint = memory_acl.memory_created_at == memory_shard.created_at @ AgentMemoryError::AccessNotGranted,
+    )]
+    pub memory_acl: Option<Account<'info, MemoryAcl>>,
+    
+    /// Sharing group of the vault `authority` belongs to
+    #[account(
+        seeds = [b"group", vault.key().as_ref(), sharing_group.seed_name.as_bytes()],
+        bump = sharing_group.bump,
+        has_one = vault,
+    )]
+    pub sharing_group: Option<Account<'info, SharingGroup>>,
+    
+    /// Membership record of `authority` in `sharing_group`
+    #[account(
+        seeds = [b"group_member", group_member.group.as_ref(), authority.key().as_ref()],
+        bump = group_member.bump,
+    )]
+    pub group_member: Option<Account<'info, GroupMember>>,
+    
+    /// CHECK: Protocol config for pause state
+    #[account(
+        seeds = [b"config"],
+        bump = protocol_config.bump,
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct PermanentDeleteMemory<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"memory", vault.memory_root.as_ref(), memory_shard.key.as_bytes()],
+        bump = memory_shard.bump,
+        constraint = memory_shard.is_deleted @ AgentMemoryError::MemoryNotDeleted,
+    )]
+    pub memory_shard: Account<'info, MemoryShard>,
+    
+    /// Approved proposal, required when the vault has an approver set
+    #[account(
+        mut,
+        seeds = [b"proposal", vault.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Option<Account<'info, VaultProposal>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RollbackMemory<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    #[account(
+        mut,
+        seeds = [b"memory", vault.memory_root.as_ref(), memory_shard.key.as_bytes()],
+        bump = memory_shard.bump,
+        constraint = !memory_shard.is_deleted @ AgentMemoryError::MemoryAlreadyDeleted,
+    )]
+    pub memory_shard: Account<'info, MemoryShard>,
+    
+    /// CHECK: Protocol config for pause state
+    #[account(
+        seeds = [b"config"],
+        bump = protocol_config.bump,
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RestoreMemory<'info> {
+    /// Vault owner, an authorized delegate, a permitted grantee or group member
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    #[account(
+        mut,
+        seeds = [b"memory", vault.memory_root.as_ref(), memory_shard.key.as_bytes()],
+        bump = memory_shard.bump,
+        constraint = memory_shard.is_deleted @ AgentMemoryError::MemoryNotDeleted,
+    )]
+    pub memory_shard: Account<'info, MemoryShard>,
+    
+    /// Required when `authority` is a delegate rather than the vault owner
+    #[account(
+        mut,
+        seeds = [b"delegate", vault.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump,
+    )]
+    pub delegate: Option<Account<'info, SessionDelegate>>,
+    
+    /// Access grant of `authority` when it is not the owner or a delegate
+    #[account(
+        mut,
+        seeds = [b"access", vault.key().as_ref(), authority.key().as_ref()],
+        bump = access_grant.bump,
+    )]
+    pub access_grant: Option<Account<'info, AccessGrant>>,
+    
+    /// Shard-level ACL of `authority`, checked before `access_grant`
+    #[account(
+        seeds = [b"acl", memory_shard.key().as_ref(), authority.key().as_ref()],
+        bump = memory_acl.bump,
+        constraint = memory_acl.memory_created_at == memory_shard.created_at @ AgentMemoryError::AccessNotGranted,
+    )]
+    pub memory_acl: Option<Account<'info, MemoryAcl>>,
+    
+    /// Sharing group of the vault `authority` belongs to
+    #[account(
+        seeds = [b"group", vault.key().as_ref(), sharing_group.seed_name.as_bytes()],
+        bump = sharing_group.bump,
+        has_one = vault,
+    )]
+    pub sharing_group: Option<Account<'info, SharingGroup>>,
+    
+    /// Membership record of `authority` in `sharing_group`
+    #[account(
+        seeds = [b"group_member", group_member.group.as_ref(), authority.key().as_ref()],
+        bump = group_member.bump,
+    )]
+    pub group_member: Option<Account<'info, GroupMember>>,
+    
+    /// CHECK: Protocol config for pause state
+    #[account(
+        seeds = [b"config"],
+        bump = protocol_config.bump,
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProfile<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"profile", agent_profile.agent_key.as_ref()],
+        bump = agent_profile.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+}
+
+#[derive(Accounts)]
+pub struct RecordTask<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"profile", agent_profile.agent_key.as_ref()],
+        bump = agent_profile.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+}
+
+#[derive(Accounts)]
+#[instruction(permission_level: PermissionLevel)]
+pub struct GrantAccess<'info> {
+    /// Vault owner or a grantee allowed to manage access
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    /// Access grant of `authority` when it is not the owner
+    #[account(
+        seeds = [b"access", vault.key().as_ref(), authority.key().as_ref()],
+        bump = authority_grant.bump,
+    )]
+    pub authority_grant: Option<Account<'info, AccessGrant>>,
+    
+    /// CHECK: The agent being granted access
+    pub grantee: AccountInfo<'info>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AccessGrant::INIT_SPACE,
+        seeds = [b"access", vault.key().as_ref(), grantee.key().as_ref()],
+        bump
+    )]
+    pub access_grant: Account<'info, AccessGrant>,
+    
+    /// CHECK: Protocol config for pause state
+    #[account(
+        seeds = [b"config"],
+        bump = protocol_config.bump,
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    
+    /// Approved proposal, required when the vault has an approver set
+    #[account(
+        mut,
+        seeds = [b"proposal", vault.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Option<Account<'info, VaultProposal>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAccess<'info> {
+    /// Vault owner or a grantee allowed to manage access
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    /// Access grant of `authority` when it is not the owner
+    #[account(
+        seeds = [b"access", vault.key().as_ref(), authority.key().as_ref()],
+        bump = authority_grant.bump,
+    )]
+    pub authority_grant: Option<Account<'info, AccessGrant>>,
+    
+    #[account(
+        mut,
+        seeds = [b"access", vault.key().as_ref(), access_grant.grantee.as_ref()],
+        bump = access_grant.bump,
+    )]
+    pub access_grant: Account<'info, AccessGrant>,
+    
+    /// Approved proposal, required when the vault has an approver set
+    #[account(
+        mut,
+        seeds = [b"proposal", vault.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Option<Account<'info, VaultProposal>>,
+    
+    /// CHECK: Protocol config for pause state
+    #[account(
+        seeds = [b"config"],
+        bump = protocol_config.bump,
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAccess<'info> {
+    /// Vault owner or a grantee allowed to manage access
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    /// Access grant of `authority` when it is not the owner
+    #[account(
+        seeds = [b"access", vault.key().as_ref(), authority.key().as_ref()],
+        bump = authority_grant.bump,
+    )]
+    pub authority_grant: Option<Account<'info, AccessGrant>>,
+    
+    #[account(
+        mut,
+        seeds = [b"access", vault.key().as_ref(), access_grant.grantee.as_ref()],
+        bump = access_grant.bump,
+    )]
+    pub access_grant: Account<'info, AccessGrant>,
+    
+    /// Approved proposal, required when the vault has an approver set
+    #[account(
+        mut,
+        seeds = [b"proposal", vault.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Option<Account<'info, VaultProposal>>,
+    
+    /// CHECK: Protocol config for pause state
+    #[account(
+        seeds = [b"config"],
+        bump = protocol_config.bump,
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CloseAccessGrant<'info> {
+    pub owner: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    /// CHECK: Receives the grant rent; must be the account that paid it
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"access", vault.key().as_ref(), access_grant.grantee.as_ref()],
+        bump = access_grant.bump,
+        has_one = payer,
+    )]
+    pub access_grant: Account<'info, AccessGrant>,
+}
+
+#[derive(Accounts)]
+pub struct CloseExpiredAccessGrant<'info> {
+    pub caller: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
     )]
-    pub memory_shard: Account<'info, MemoryShard>,
+    pub vault: Account<'info, MemoryVault>,
+    
+    /// CHECK: Receives the grant rent; must be the account that paid it
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
     
-    /// CHECK: Protocol config for pause state
     #[account(
-        seeds = [b"config"],
-        bump = protocol_config.bump,
+        mut,
+        close = payer,
+        seeds = [b"access", vault.key().as_ref(), access_grant.grantee.as_ref()],
+        bump = access_grant.bump,
+        has_one = payer,
     )]
-    pub protocol_config: Account<'info, ProtocolConfig>,
+    pub access_grant: Account<'info, AccessGrant>,
 }
 
 #[derive(Accounts)]
-pub struct DeleteMemory<'info> {
-    #[account(mut)]
-    pub owner: Signer<'info>,
+pub struct RecordMemoryRead<'info> {
+    pub authority: Signer<'info>,
     
     #[account(
         mut,
-        seeds = [b"vault", vault.owner.as_ref(), vault.agent_key.as_ref()],
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
         bump = vault.bump,
-        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
     )]
     pub vault: Account<'info, MemoryVault>,
     
     #[account(
-        mut,
-        seeds = [b"memory", vault.key().as_ref(), memory_shard.key.as_bytes()],
+        seeds = [b"memory", vault.memory_root.as_ref(), memory_shard.key.as_bytes()],
         bump = memory_shard.bump,
         constraint = !memory_shard.is_deleted @ AgentMemoryError::MemoryAlreadyDeleted,
     )]
     pub memory_shard: Account<'info, MemoryShard>,
     
-    /// CHECK: Protocol config for pause state
     #[account(
-        seeds = [b"config"],
-        bump = protocol_config.bump,
+        mut,
+        seeds = [b"access", vault.key().as_ref(), authority.key().as_ref()],
+        bump = access_grant.bump,
     )]
-    pub protocol_config: Account<'info, ProtocolConfig>,
+    pub access_grant: Account<'info, AccessGrant>,
 }
 
 #[derive(Accounts)]
-pub struct PermanentDeleteMemory<'info> {
+pub struct SetMemoryAcl<'info> {
+    /// Vault owner or a grantee allowed to manage access
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub authority: Signer<'info>,
     
     #[account(
         mut,
-        seeds = [b"vault", vault.owner.as_ref(), vault.agent_key.as_ref()],
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
         bump = vault.bump,
-        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
     )]
     pub vault: Account<'info, MemoryVault>,
     
+    /// Access grant of `authority` when it is not the owner
     #[account(
-        mut,
-        close = owner,
-        seeds = [b"memory", vault.key().as_ref(), memory_shard.key.as_bytes()],
+        seeds = [b"access", vault.key().as_ref(), authority.key().as_ref()],
+        bump = authority_grant.bump,
+    )]
+    pub authority_grant: Option<Account<'info, AccessGrant>>,
+    
+    #[account(
+        seeds = [b"memory", vault.memory_root.as_ref(), memory_shard.key.as_bytes()],
         bump = memory_shard.bump,
-        constraint = memory_shard.is_deleted @ AgentMemoryError::MemoryNotDeleted,
+        constraint = !memory_shard.is_deleted @ AgentMemoryError::MemoryAlreadyDeleted,
     )]
     pub memory_shard: Account<'info, MemoryShard>,
     
-    pub system_program: Program<'info, System>,
-}
-
-#[derive(Accounts)]
-pub struct RollbackMemory<'info> {
-    #[account(mut)]
-    pub owner: Signer<'info>,
+    /// CHECK: The agent the shard is shared with
+    pub grantee: AccountInfo<'info>,
     
     #[account(
-        seeds = [b"vault", vault.owner.as_ref(), vault.agent_key.as_ref()],
-        bump = vault.bump,
-        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+        init_if_needed,
+        payer = authority,
+        space = 8 + MemoryAcl::INIT_SPACE,
+        seeds = [b"acl", memory_shard.key().as_ref(), grantee.key().as_ref()],
+        bump
     )]
-    pub vault: Account<'info, MemoryVault>,
+    pub memory_acl: Account<'info, MemoryAcl>,
     
+    /// Approved proposal, required when the vault has an approver set
     #[account(
         mut,
-        seeds = [b"memory", vault.key().as_ref(), memory_shard.key.as_bytes()],
-        bump = memory_shard.bump,
-        constraint = !memory_shard.is_deleted @ AgentMemoryError::MemoryAlreadyDeleted,
+        seeds = [b"proposal", vault.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
     )]
-    pub memory_shard: Account<'info, MemoryShard>,
+    pub proposal: Option<Account<'info, VaultProposal>>,
     
     /// CHECK: Protocol config for pause state
     #[account(
@@ -834,106 +2672,120 @@ pub struct RollbackMemory<'info> {
         bump = protocol_config.bump,
     )]
     pub protocol_config: Account<'info, ProtocolConfig>,
+    
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct RestoreMemory<'info> {
-    #[account(mut)]
-    pub owner: Signer<'info>,
+pub struct RemoveMemoryAcl<'info> {
+    /// Vault owner or the issuer of the entry
+    pub authority: Signer<'info>,
     
     #[account(
         mut,
-        seeds = [b"vault", vault.owner.as_ref(), vault.agent_key.as_ref()],
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
         bump = vault.bump,
-        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
     )]
     pub vault: Account<'info, MemoryVault>,
     
+    /// CHECK: Receives the entry rent; must be the account that paid it
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
+    
     #[account(
         mut,
-        seeds = [b"memory", vault.key().as_ref(), memory_shard.key.as_bytes()],
-        bump = memory_shard.bump,
+        close = payer,
+        seeds = [b"acl", memory_acl.memory.as_ref(), memory_acl.grantee.as_ref()],
+        bump = memory_acl.bump,
+        has_one = vault,
+        has_one = payer,
     )]
-    pub memory_shard: Account<'info, MemoryShard>,
+    pub memory_acl: Account<'info, MemoryAcl>,
     
-    /// CHECK: Protocol config for pause state
+    /// Approved proposal, required when the vault has an approver set
     #[account(
-        seeds = [b"config"],
-        bump = protocol_config.bump,
+        mut,
+        seeds = [b"proposal", vault.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
     )]
-    pub protocol_config: Account<'info, ProtocolConfig>,
+    pub proposal: Option<Account<'info, VaultProposal>>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateProfile<'info> {
+#[instruction(name: String)]
+pub struct CreateSharingGroup<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
     
     #[account(
         mut,
-        seeds = [b"profile", agent_profile.agent_key.as_ref()],
-        bump = agent_profile.bump,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
         has_one = owner @ AgentMemoryError::UnauthorizedOwner,
     )]
-    pub agent_profile: Account<'info, AgentProfile>,
-}
-
-#[derive(Accounts)]
-pub struct RecordTask<'info> {
-    #[account(mut)]
-    pub owner: Signer<'info>,
+    pub vault: Account<'info, MemoryVault>,
     
     #[account(
-        mut,
-        seeds = [b"profile", agent_profile.agent_key.as_ref()],
-        bump = agent_profile.bump,
-        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+        init,
+        payer = owner,
+        space = 8 + SharingGroup::INIT_SPACE,
+        seeds = [b"group", vault.key().as_ref(), name.as_bytes()],
+        bump
     )]
-    pub agent_profile: Account<'info, AgentProfile>,
+    pub sharing_group: Account<'info, SharingGroup>,
+    
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(permission_level: PermissionLevel)]
-pub struct GrantAccess<'info> {
+#[instruction(member: Pubkey)]
+pub struct AddGroupMember<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
     
     #[account(
-        seeds = [b"vault", vault.owner.as_ref(), vault.agent_key.as_ref()],
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
         bump = vault.bump,
         has_one = owner @ AgentMemoryError::UnauthorizedOwner,
     )]
     pub vault: Account<'info, MemoryVault>,
     
-    /// CHECK: The agent being granted access
-    pub grantee: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"group", vault.key().as_ref(), sharing_group.seed_name.as_bytes()],
+        bump = sharing_group.bump,
+        has_one = vault,
+    )]
+    pub sharing_group: Account<'info, SharingGroup>,
     
     #[account(
         init,
         payer = owner,
-        space = 8 + AccessGrant::INIT_SPACE,
-        seeds = [b"access", vault.key().as_ref(), grantee.key().as_ref()],
+        space = 8 + GroupMember::INIT_SPACE,
+        seeds = [b"group_member", sharing_group.key().as_ref(), member.as_ref()],
         bump
     )]
-    pub access_grant: Account<'info, AccessGrant>,
+    pub group_member: Account<'info, GroupMember>,
     
-    /// CHECK: Protocol config for pause state
+    /// Approved proposal, required when the vault has an approver set
     #[account(
-        seeds = [b"config"],
-        bump = protocol_config.bump,
+        mut,
+        seeds = [b"proposal", vault.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
     )]
-    pub protocol_config: Account<'info, ProtocolConfig>,
+    pub proposal: Option<Account<'info, VaultProposal>>,
     
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct RevokeAccess<'info> {
-    #[account(mut)]
+pub struct RemoveGroupMember<'info> {
     pub owner: Signer<'info>,
     
     #[account(
-        seeds = [b"vault", vault.owner.as_ref(), vault.agent_key.as_ref()],
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
         bump = vault.bump,
         has_one = owner @ AgentMemoryError::UnauthorizedOwner,
     )]
@@ -941,52 +2793,100 @@ pub struct RevokeAccess<'info> {
     
     #[account(
         mut,
-        seeds = [b"access", vault.key().as_ref(), access_grant.grantee.as_ref()],
-        bump = access_grant.bump,
+        seeds = [b"group", vault.key().as_ref(), sharing_group.seed_name.as_bytes()],
+        bump = sharing_group.bump,
+        has_one = vault,
     )]
-    pub access_grant: Account<'info, AccessGrant>,
+    pub sharing_group: Account<'info, SharingGroup>,
+    
+    /// CHECK: Receives the record rent; must be the account that paid it
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
     
-    /// CHECK: Protocol config for pause state
     #[account(
-        seeds = [b"config"],
-        bump = protocol_config.bump,
+        mut,
+        close = payer,
+        seeds = [b"group_member", sharing_group.key().as_ref(), group_member.member.as_ref()],
+        bump = group_member.bump,
+        constraint = group_member.group == sharing_group.key() @ AgentMemoryError::MemberNotFound,
+        has_one = payer,
     )]
-    pub protocol_config: Account<'info, ProtocolConfig>,
+    pub group_member: Account<'info, GroupMember>,
 }
 
 #[derive(Accounts)]
-#[instruction(name: String)]
-pub struct CreateSharingGroup<'info> {
+pub struct LeaveGroup<'info> {
+    pub member: Signer<'info>,
+    
     #[account(mut)]
+    pub sharing_group: Account<'info, SharingGroup>,
+    
+    /// CHECK: Receives the record rent; must be the account that paid it
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"group_member", sharing_group.key().as_ref(), member.key().as_ref()],
+        bump = group_member.bump,
+        constraint = group_member.group == sharing_group.key() @ AgentMemoryError::MemberNotFound,
+        has_one = payer,
+    )]
+    pub group_member: Account<'info, GroupMember>,
+}
+
+#[derive(Accounts)]
+pub struct ManageSharingGroup<'info> {
     pub owner: Signer<'info>,
     
     #[account(
-        seeds = [b"vault", vault.owner.as_ref(), vault.agent_key.as_ref()],
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
         bump = vault.bump,
         has_one = owner @ AgentMemoryError::UnauthorizedOwner,
     )]
     pub vault: Account<'info, MemoryVault>,
     
     #[account(
-        init,
-        payer = owner,
-        space = 8 + SharingGroup::INIT_SPACE,
-        seeds = [b"group", vault.key().as_ref(), name.as_bytes()],
-        bump
+        mut,
+        seeds = [b"group", vault.key().as_ref(), sharing_group.seed_name.as_bytes()],
+        bump = sharing_group.bump,
+        has_one = vault,
     )]
     pub sharing_group: Account<'info, SharingGroup>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptGroupCreator<'info> {
+    pub new_creator: Signer<'info>,
     
-    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        constraint = sharing_group.pending_creator == Some(new_creator.key()) @ AgentMemoryError::NoPendingCreator,
+    )]
+    pub sharing_group: Account<'info, SharingGroup>,
 }
 
 #[derive(Accounts)]
-pub struct ManageGroupMember<'info> {
+pub struct CloseSharingGroup<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
     
     #[account(
         mut,
-        constraint = sharing_group.creator == owner.key() @ AgentMemoryError::NotGroupCreator,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"group", vault.key().as_ref(), sharing_group.seed_name.as_bytes()],
+        bump = sharing_group.bump,
+        has_one = vault,
     )]
     pub sharing_group: Account<'info, SharingGroup>,
 }
@@ -1017,7 +2917,8 @@ pub struct BatchCreateMemories<'info> {
     pub owner: Signer<'info>,
     
     #[account(
-        seeds = [b"vault", vault.owner.as_ref(), vault.agent_key.as_ref()],
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
         bump = vault.bump,
         has_one = owner @ AgentMemoryError::UnauthorizedOwner,
     )]
@@ -1037,7 +2938,8 @@ pub struct BatchDeleteMemories<'info> {
     pub owner: Signer<'info>,
     
     #[account(
-        seeds = [b"vault", vault.owner.as_ref(), vault.agent_key.as_ref()],
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
         bump = vault.bump,
         has_one = owner @ AgentMemoryError::UnauthorizedOwner,
     )]
@@ -1057,7 +2959,7 @@ pub struct BatchUpdateTags<'info> {
     pub owner: Signer<'info>,
     
     #[account(
-        seeds = [b"vault", vault.owner.as_ref(), vault.agent_key.as_ref()],
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
         bump = vault.bump,
         has_one = owner @ AgentMemoryError::UnauthorizedOwner,
     )]
@@ -1072,7 +2974,7 @@ pub struct StakeForStorage<'info> {
     
     #[account(
         mut,
-        seeds = [b"vault", vault.owner.as_ref(), vault.agent_key.as_ref()],
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
         bump = vault.bump,
         has_one = owner @ AgentMemoryError::UnauthorizedOwner,
     )]
@@ -1112,7 +3014,7 @@ pub struct UnstakeTokens<'info> {
     
     #[account(
         mut,
-        seeds = [b"vault", vault.owner.as_ref(), vault.agent_key.as_ref()],
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
         bump = vault.bump,
         has_one = owner @ AgentMemoryError::UnauthorizedOwner,
     )]
@@ -1141,7 +3043,7 @@ pub struct ClaimRewards<'info> {
     
     #[account(
         mut,
-        seeds = [b"vault", vault.owner.as_ref(), vault.agent_key.as_ref()],
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
         bump = vault.bump,
         has_one = owner @ AgentMemoryError::UnauthorizedOwner,
     )]
@@ -1280,7 +3182,7 @@ pub enum AgentMemoryError {
     #[msg("Group description too long (max 256 characters)")]
     GroupDescTooLong,
     
-    #[msg("Group too large (max 50 members)")]
+    #[msg("Group too large")]
     GroupTooLarge,
     
     #[msg("Member already exists in group")]
@@ -1336,4 +3238,340 @@ pub enum AgentMemoryError {
     
     #[msg("Arithmetic overflow occurred")]
     ArithmeticOverflow,
+    
+    #[msg("Vault still holds memories")]
+    VaultNotEmpty,
+    
+    #[msg("Vault still holds staked tokens")]
+    VaultHasStake,
+    
+    #[msg("Vault still has active access grants")]
+    VaultHasActiveGrants,
+    
+    #[msg("Vault is frozen")]
+    VaultFrozen,
+    
+    #[msg("Invalid new owner")]
+    InvalidNewOwner,
+    
+    #[msg("No pending ownership transfer for this signer")]
+    NoPendingOwner,
+    
+    #[msg("Account has been migrated to a new agent key")]
+    AlreadyMigrated,
+    
+    #[msg("Signer is neither the vault owner nor an authorized delegate")]
+    UnauthorizedDelegate,
+    
+    #[msg("Delegate authorization expired")]
+    DelegateExpired,
+    
+    #[msg("Memory type not allowed for this delegate")]
+    DelegateScopeViolation,
+    
+    #[msg("Delegate daily write allowance exceeded")]
+    DelegateQuotaExceeded,
+    
+    #[msg("Vault storage quota exceeded")]
+    QuotaExceeded,
+    
+    #[msg("Invalid eviction candidate")]
+    InvalidEvictionCandidate,
+    
+    #[msg("Account is not a live memory shard of this vault")]
+    InvalidShardAccount,
+    
+    #[msg("Vault changed during reconciliation; restart it")]
+    ReconciliationStale,
+    
+    #[msg("Empty snapshot label not allowed")]
+    EmptySnapshotLabel,
+    
+    #[msg("Snapshot label too long (max 32 characters)")]
+    SnapshotLabelTooLong,
+    
+    #[msg("Too many approvers (max 10)")]
+    TooManyApprovers,
+    
+    #[msg("Invalid approval threshold")]
+    InvalidApprovalThreshold,
+    
+    #[msg("Duplicate approver")]
+    DuplicateApprover,
+    
+    #[msg("Signer is not a vault approver")]
+    NotApprover,
+    
+    #[msg("Approver already approved this proposal")]
+    AlreadyApproved,
+    
+    #[msg("Multisig approval required for this operation")]
+    MultisigApprovalRequired,
+    
+    #[msg("Proposal has expired")]
+    ProposalExpired,
+    
+    #[msg("Proposal already executed")]
+    ProposalAlreadyExecuted,
+    
+    #[msg("Proposal does not match this operation")]
+    ProposalActionMismatch,
+    
+    #[msg("Not enough approvals")]
+    InsufficientApprovals,
+    
+    #[msg("Proposal is still pending")]
+    ProposalStillPending,
+    
+    #[msg("Too many guardians (max 10)")]
+    TooManyGuardians,
+    
+    #[msg("Invalid guardian threshold")]
+    InvalidGuardianThreshold,
+    
+    #[msg("Guardian must be unique and not the vault owner")]
+    InvalidGuardian,
+    
+    #[msg("Invalid recovery delay (1 to 30 days)")]
+    InvalidRecoveryDelay,
+    
+    #[msg("Signer is not a vault guardian")]
+    NotGuardian,
+    
+    #[msg("Recovery veto delay has not elapsed")]
+    RecoveryDelayActive,
+    
+    #[msg("Invalid beneficiary")]
+    InvalidBeneficiary,
+    
+    #[msg("Invalid inactivity period (7 days to 5 years)")]
+    InvalidInactivityPeriod,
+    
+    #[msg("No inheritance configured for this beneficiary")]
+    InheritanceNotConfigured,
+    
+    #[msg("Owner has been active within the inactivity period")]
+    OwnerStillActive,
+    
+    #[msg("Account required by the inheritance mode is missing")]
+    MissingInheritanceAccount,
+    
+    #[msg("Access grant is still active")]
+    GrantStillActive,
+    
+    #[msg("Memory is outside the access grant scope")]
+    GrantScopeViolation,
+    
+    #[msg("Invalid grant scope")]
+    InvalidGrantScope,
+    
+    #[msg("Sharing group is inactive")]
+    GroupInactive,
+    
+    #[msg("Sharing group still has members")]
+    GroupNotEmpty,
+    
+    #[msg("Group key does not belong to the sharing group")]
+    InvalidGroupKey,
+    
+    #[msg("Group key epoch is outdated; rotate the group key")]
+    StaleGroupKey,
+    
+    #[msg("Too many wrapped keys in one instruction")]
+    TooManyWrappedKeys,
+    
+    #[msg("Sub-grant exceeds the permissions, scope or expiry of its parent grant")]
+    SubGrantExceedsParent,
+    
+    #[msg("Sub-grant chain too deep")]
+    GrantDepthExceeded,
+    
+    #[msg("Missing or invalid ancestor grant")]
+    InvalidGrantChain,
+    
+    #[msg("Grant was not issued under the signer's grant")]
+    NotGrantIssuer,
+    
+    #[msg("Access grant is not valid yet")]
+    AccessNotYetValid,
+    
+    #[msg("Invalid grant usage limit")]
+    InvalidMaxUses,
+    
+    #[msg("Sharing group still has open group key epochs")]
+    GroupKeysOpen,
+    
+    #[msg("Group key epoch is still in use")]
+    GroupKeyInUse,
+    
+    #[msg("Issued under a previous vault owner")]
+    StaleOwnerEpoch,
+    
+    #[msg("Recovery request has expired")]
+    RecoveryExpired,
+    
+    #[msg("Recovery request is still open")]
+    RecoveryStillOpen,
+    
+    #[msg("Account is not a memory vault of this program")]
+    InvalidVaultAccount,
+    
+    #[msg("Vault is not in a layout that can be migrated")]
+    UnsupportedVaultLayout,
+    
+    #[msg("No pending creator transfer")]
+    NoPendingCreator,
+    
+    #[msg("Invalid new group creator")]
+    InvalidNewCreator,
+    
+    #[msg("Account is not in a layout that can be migrated")]
+    UnsupportedAccountLayout,
+    
+    #[msg("Account does not belong to this vault")]
+    InvalidMigrationAccount,
+    
+    #[msg("Snapshot is sealed")]
+    SnapshotSealed,
+    
+    #[msg("Vault still has open delegates, grants, ACL entries, groups, snapshots, proposals or recovery requests")]
+    VaultHasOpenAccounts,
+    
+    #[msg("Reconciliation does not cover every shard of the vault")]
+    ReconciliationIncomplete,
+    
+    #[msg("Usage-limited grants cannot manage access or issue sub-grants")]
+    UsageLimitedGrant,
 }
+    
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn metadata(memory_type: MemoryType, tags: [u8; 8]) -> MemoryMetadata {
+        MemoryMetadata {
+            memory_type,
+            importance: 50,
+            tags,
+            ipfs_cid: None,
+        }
+    }
+    
+    /// Returns an active vault with no guardians or approvers
+    pub(crate) fn vault() -> MemoryVault {
+        let owner = Pubkey::new_unique();
+        MemoryVault {
+            owner,
+            layout_version: VAULT_LAYOUT_VERSION,
+            seed_owner: owner,
+            pending_owner: None,
+            owner_epoch: 0,
+            agent_key: Pubkey::new_unique(),
+            encryption_pubkey: [0; 32],
+            created_at: 0,
+            updated_at: 0,
+            memory_count: 0,
+            total_memory_size: 0,
+            deleted_memory_count: 0,
+            shards_created: 0,
+            shards_closed: 0,
+            staked_amount: 0,
+            reward_points: 0,
+            active_grant_count: 0,
+            open_account_count: 0,
+            max_memory_count: 0,
+            max_total_bytes: 0,
+            eviction_enabled: false,
+            approvers: vec![],
+            approval_threshold: 0,
+            proposal_count: 0,
+            guardians: vec![],
+            guardian_threshold: 0,
+            recovery_delay: 0,
+            beneficiary: None,
+            inactivity_period: 0,
+            inheritance_mode: InheritanceMode::GrantAdmin,
+            last_heartbeat: 0,
+            memory_root: Pubkey::default(),
+            migrated_to: None,
+            is_active: true,
+            bump: 255,
+        }
+    }
+    
+    /// Wraps `value` in a program-owned account at a fresh address
+    pub(crate) fn account<T: AccountSerialize + AccountDeserialize + Owner + Clone>(
+        value: &T,
+    ) -> Account<'static, T> {
+        let mut data = Vec::new();
+        value.try_serialize(&mut data).unwrap();
+        let info = Box::leak(Box::new(AccountInfo::new(
+            Box::leak(Box::new(Pubkey::new_unique())),
+            false,
+            false,
+            Box::leak(Box::new(1)),
+            Box::leak(data.into_boxed_slice()),
+            &crate::ID,
+            false,
+            0,
+        )));
+        Account::try_from(&*info).unwrap()
+    }
+    
+    fn scope(key_prefix: &str, memory_types: u8, required_tags: &[u8]) -> GrantScope {
+        GrantScope {
+            key_prefix: key_prefix.to_string(),
+            memory_types,
+            required_tags: required_tags.to_vec(),
+        }
+    }
+    
+    #[test]
+    fn default_scope_allows_everything() {
+        let scope = GrantScope::default();
+    
+        assert!(scope.allows("", &metadata(MemoryType::System, [0; 8])));
+        assert!(scope.allows("any/key", &metadata(MemoryType::Task, [3; 8])));
+    }
+    
+    #[test]
+    fn scope_checks_prefix_type_and_tags() {
+        let scope = scope("notes/", MemoryType::Task.mask(), &[2, 5]);
+        let tagged = [2, 5, 0, 0, 0, 0, 0, 0];
+    
+        assert!(scope.allows("notes/today", &metadata(MemoryType::Task, tagged)));
+        assert!(!scope.allows("note", &metadata(MemoryType::Task, tagged)));
+        assert!(!scope.allows("notes/today", &metadata(MemoryType::Learning, tagged)));
+        assert!(!scope.allows("notes/today", &metadata(MemoryType::Task, [2, 0, 0, 0, 0, 0, 0, 0])));
+    }
+    
+    #[test]
+    fn scope_allows_any_listed_type() {
+        let scope = scope("", MemoryType::Task.mask() | MemoryType::Learning.mask(), &[]);
+    
+        assert!(scope.allows("a", &metadata(MemoryType::Task, [0; 8])));
+        assert!(scope.allows("a", &metadata(MemoryType::Learning, [0; 8])));
+        assert!(!scope.allows("a", &metadata(MemoryType::System, [0; 8])));
+    }
+    
+    #[test]
+    fn narrower_scope_is_within_parent() {
+        let parent = scope("notes/", MemoryType::Task.mask() | MemoryType::Learning.mask(), &[2]);
+    
+        assert!(parent.is_within(&parent));
+        assert!(scope("notes/work/", MemoryType::Task.mask(), &[2, 5]).is_within(&parent));
+        assert!(parent.is_within(&GrantScope::default()));
+        assert!(scope("notes/", 0, &[]).is_within(&scope("", 0, &[])));
+    }
+    
+    #[test]
+    fn wider_scope_is_not_within_parent() {
+        let parent = scope("notes/", MemoryType::Task.mask(), &[2]);
+    
+        assert!(!scope("", MemoryType::Task.mask(), &[2]).is_within(&parent));
+        assert!(!scope("notes/", 0, &[2]).is_within(&parent));
+        assert!(!scope("notes/", MemoryType::Task.mask() | MemoryType::System.mask(), &[2]).is_within(&parent));
+        assert!(!scope("notes/", MemoryType::Task.mask(), &[]).is_within(&parent));
+        assert!(!GrantScope::default().is_within(&parent));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,580 @@
+use anchor_lang::prelude::*;
+use crate::{
+    AgentMemoryError, GrantScope, InheritanceMode, MemoryVault, PermissionLevel, MAX_GUARDIANS,
+};
+
+// ============================================================================
+// VAULT MULTISIG MODULE
+// ============================================================================
+// Optional M-of-N approval for destructive vault operations.
+// Once a vault has an approver set, permanent deletes, grant changes,
+// group member additions, ownership transfer and approver changes must
+// present an approved
+// `VaultProposal` describing exactly that action. Routine memory writes
+// remain single-signer.
+
+/// Maximum number of approvers on a vault
+pub const MAX_APPROVERS: usize = 10;
+
+/// Maximum lifetime of a proposal (30 days)
+pub const MAX_PROPOSAL_LIFETIME: i64 = 30 * 24 * 60 * 60;
+
+/// Proposal for a multisig-gated vault action
+#[account]
+#[derive(InitSpace)]
+pub struct VaultProposal {
+    /// The vault the action applies to
+    pub vault: Pubkey,
+    /// Sequential id within the vault
+    pub proposal_id: u64,
+    /// Owner or approver who created the proposal (receives rent on close)
+    pub proposer: Pubkey,
+    /// The action being approved
+    pub action: ProposalAction,
+    /// Approvers who have signed off
+    #[max_len(MAX_APPROVERS)]
+    pub approvals: Vec<Pubkey>,
+    /// Unix timestamp when the proposal was created
+    pub created_at: i64,
+    /// Unix timestamp after which the proposal can no longer be executed
+    pub expires_at: i64,
+    /// Whether the action has been carried out
+    pub executed: bool,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+/// Vault actions that require multisig approval
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, InitSpace)]
+pub enum ProposalAction {
+    PermanentDeleteMemory {
+        memory: Pubkey,
+    },
+    GrantAccess {
+        grantee: Pubkey,
+        permission_level: PermissionLevel,
+        expires_at: Option<i64>,
+        scope: Option<GrantScope>,
+        permissions: Option<u8>,
+        not_before: Option<i64>,
+        max_uses: Option<u32>,
+    },
+    RevokeAccess {
+        grantee: Pubkey,
+    },
+    SetMemoryAcl {
+        memory: Pubkey,
+        grantee: Pubkey,
+        permission_level: PermissionLevel,
+        expires_at: Option<i64>,
+        permissions: Option<u8>,
+    },
+    RemoveMemoryAcl {
+        memory: Pubkey,
+        grantee: Pubkey,
+    },
+    TransferOwnership {
+        new_owner: Pubkey,
+    },
+    SetApprovers {
+        #[max_len(MAX_APPROVERS)]
+        approvers: Vec<Pubkey>,
+        threshold: u8,
+    },
+    SetGuardians {
+        #[max_len(MAX_GUARDIANS)]
+        guardians: Vec<Pubkey>,
+        threshold: u8,
+        delay: i64,
+    },
+    SetInheritance {
+        beneficiary: Option<Pubkey>,
+        inactivity_period: i64,
+        mode: InheritanceMode,
+    },
+    AddGroupMember {
+        group: Pubkey,
+        member: Pubkey,
+        permission: PermissionLevel,
+        permissions: Option<u8>,
+    },
+    UpdateAccess {
+        grant: Pubkey,
+        permission_level: PermissionLevel,
+        expires_at: Option<i64>,
+        scope: Option<GrantScope>,
+        permissions: Option<u8>,
+        not_before: Option<i64>,
+        max_uses: Option<u32>,
+    },
+}
+
+impl VaultProposal {
+    /// Number of approvals from keys that are still vault approvers
+    pub fn valid_approvals(&self, vault: &MemoryVault) -> usize {
+        self.approvals
+            .iter()
+            .filter(|approver| vault.approvers.contains(approver))
+            .count()
+    }
+}
+
+/// Check multisig approval for `action` and mark the proposal executed
+///
+/// Vaults without an approver set pass unconditionally. Otherwise an
+/// unexpired, unexecuted proposal for exactly this action with at least
+/// `approval_threshold` current approvals is required.
+pub fn require_approval(
+    vault: &Account<MemoryVault>,
+    proposal: Option<&mut Account<VaultProposal>>,
+    action: &ProposalAction,
+    executed_by: Pubkey,
+    now: i64,
+) -> Result<()> {
+    if vault.approval_threshold == 0 {
+        return Ok(());
+    }
+
+    let proposal = proposal.ok_or(AgentMemoryError::MultisigApprovalRequired)?;
+    require!(!proposal.executed, AgentMemoryError::ProposalAlreadyExecuted);
+    require!(now < proposal.expires_at, AgentMemoryError::ProposalExpired);
+    require!(
+        proposal.action == *action,
+        AgentMemoryError::ProposalActionMismatch
+    );
+    require!(
+        proposal.valid_approvals(vault) >= vault.approval_threshold as usize,
+        AgentMemoryError::InsufficientApprovals
+    );
+
+    proposal.executed = true;
+
+    emit!(ProposalExecuted {
+        vault: vault.key(),
+        proposal: proposal.key(),
+        proposal_id: proposal.proposal_id,
+        executed_by,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+// ============================================================================
+// INSTRUCTIONS
+// ============================================================================
+
+/// Configure the vault approver set
+///
+/// An empty set with a zero threshold disables multisig. While a set is
+/// configured, changing it requires an approved `SetApprovers` proposal.
+pub fn set_vault_approvers(
+    ctx: Context<SetVaultApprovers>,
+    approvers: Vec<Pubkey>,
+    threshold: u8,
+) -> Result<()> {
+    require!(
+        approvers.len() <= MAX_APPROVERS,
+        AgentMemoryError::TooManyApprovers
+    );
+    require!(
+        (threshold as usize) <= approvers.len() && (threshold == 0) == approvers.is_empty(),
+        AgentMemoryError::InvalidApprovalThreshold
+    );
+    for (i, approver) in approvers.iter().enumerate() {
+        require!(
+            !approvers[..i].contains(approver),
+            AgentMemoryError::DuplicateApprover
+        );
+    }
+
+    let clock = Clock::get()?;
+    require_approval(
+        &ctx.accounts.vault,
+        ctx.accounts.proposal.as_mut(),
+        &ProposalAction::SetApprovers {
+            approvers: approvers.clone(),
+            threshold,
+        },
+        ctx.accounts.owner.key(),
+        clock.unix_timestamp,
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    vault.approvers = approvers;
+    vault.approval_threshold = threshold;
+    vault.updated_at = clock.unix_timestamp;
+    vault.last_heartbeat = clock.unix_timestamp;
+
+    emit!(VaultApproversUpdated {
+        vault: vault.key(),
+        approvers: vault.approvers.clone(),
+        threshold,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Propose a multisig-gated vault action
+pub fn propose_vault_action(
+    ctx: Context<ProposeVaultAction>,
+    action: ProposalAction,
+    expires_at: i64,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let proposer = ctx.accounts.proposer.key();
+    let clock = Clock::get()?;
+
+    require!(
+        proposer == vault.owner || vault.approvers.contains(&proposer),
+        AgentMemoryError::NotApprover
+    );
+    require!(
+        expires_at > clock.unix_timestamp,
+        AgentMemoryError::InvalidExpiration
+    );
+    require!(
+        expires_at <= clock.unix_timestamp + MAX_PROPOSAL_LIFETIME,
+        AgentMemoryError::ExpirationTooFar
+    );
+
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.vault = vault.key();
+    proposal.proposal_id = vault.proposal_count;
+    proposal.proposer = proposer;
+    proposal.action = action;
+    proposal.approvals = Vec::new();
+    proposal.created_at = clock.unix_timestamp;
+    proposal.expires_at = expires_at;
+    proposal.executed = false;
+    proposal.bump = ctx.bumps.proposal;
+
+    vault.proposal_count = vault
+        .proposal_count
+        .checked_add(1)
+        .ok_or(AgentMemoryError::Overflow)?;
+    vault.track_open_account()?;
+    vault.record_heartbeat(&proposer, clock.unix_timestamp);
+
+    emit!(ProposalCreated {
+        vault: vault.key(),
+        proposal: proposal.key(),
+        proposal_id: proposal.proposal_id,
+        proposer,
+        expires_at,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Approve a pending proposal
+pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    let proposal = &mut ctx.accounts.proposal;
+    let approver = ctx.accounts.approver.key();
+    let clock = Clock::get()?;
+
+    require!(
+        vault.approvers.contains(&approver),
+        AgentMemoryError::NotApprover
+    );
+    require!(!proposal.executed, AgentMemoryError::ProposalAlreadyExecuted);
+    require!(
+        clock.unix_timestamp < proposal.expires_at,
+        AgentMemoryError::ProposalExpired
+    );
+    require!(
+        !proposal.approvals.contains(&approver),
+        AgentMemoryError::AlreadyApproved
+    );
+
+    proposal.approvals.push(approver);
+
+    emit!(ProposalApproved {
+        vault: vault.key(),
+        proposal: proposal.key(),
+        proposal_id: proposal.proposal_id,
+        approver,
+        approvals: proposal.valid_approvals(vault) as u8,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Close an executed or expired proposal, returning rent to the proposer
+pub fn expire_proposal(ctx: Context<ExpireProposal>) -> Result<()> {
+    let proposal = &ctx.accounts.proposal;
+    let clock = Clock::get()?;
+
+    require!(
+        proposal.executed || clock.unix_timestamp >= proposal.expires_at,
+        AgentMemoryError::ProposalStillPending
+    );
+
+    ctx.accounts.vault.release_open_account();
+
+    emit!(ProposalClosed {
+        vault: proposal.vault,
+        proposal: proposal.key(),
+        proposal_id: proposal.proposal_id,
+        executed: proposal.executed,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// ============================================================================
+// CONTEXT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct SetVaultApprovers<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+
+    /// Approved proposal, required when the vault has an approver set
+    #[account(
+        mut,
+        seeds = [b"proposal", vault.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Option<Account<'info, VaultProposal>>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeVaultAction<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + VaultProposal::INIT_SPACE,
+        seeds = [b"proposal", vault.key().as_ref(), &vault.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, VaultProposal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    pub approver: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", vault.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = vault,
+    )]
+    pub proposal: Account<'info, VaultProposal>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireProposal<'info> {
+    /// CHECK: Receives the proposal rent; must be the original proposer
+    #[account(mut)]
+    pub proposer: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"proposal", vault.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = vault,
+        has_one = proposer,
+    )]
+    pub proposal: Account<'info, VaultProposal>,
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[event]
+pub struct VaultApproversUpdated {
+    pub vault: Pubkey,
+    pub approvers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub vault: Pubkey,
+    pub proposal: Pubkey,
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalApproved {
+    pub vault: Pubkey,
+    pub proposal: Pubkey,
+    pub proposal_id: u64,
+    pub approver: Pubkey,
+    pub approvals: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub vault: Pubkey,
+    pub proposal: Pubkey,
+    pub proposal_id: u64,
+    pub executed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalClosed {
+    pub vault: Pubkey,
+    pub proposal: Pubkey,
+    pub proposal_id: u64,
+    pub executed: bool,
+    pub timestamp: i64,
+}
+
+// ============================================================================
+// SDK COMPATIBILITY HELPERS
+// ============================================================================
+
+/// Derive the PDA for a vault proposal
+pub fn derive_proposal_pda(
+    vault: &Pubkey,
+    proposal_id: u64,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"proposal",
+            vault.as_ref(),
+            &proposal_id.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::account;
+
+    fn approved_vault(approvers: &[Pubkey], threshold: u8) -> Account<'static, MemoryVault> {
+        account(&MemoryVault {
+            approvers: approvers.to_vec(),
+            approval_threshold: threshold,
+            ..crate::tests::vault()
+        })
+    }
+
+    fn proposal(action: &ProposalAction, approvals: &[Pubkey]) -> Account<'static, VaultProposal> {
+        account(&VaultProposal {
+            vault: Pubkey::new_unique(),
+            proposal_id: 0,
+            proposer: approvals[0],
+            action: action.clone(),
+            approvals: approvals.to_vec(),
+            created_at: 0,
+            expires_at: 1_000,
+            executed: false,
+            bump: 255,
+        })
+    }
+
+    fn error_code<T>(result: Result<T>) -> u32 {
+        match result {
+            Err(Error::AnchorError(error)) => error.error_code_number,
+            _ => panic!("expected an Anchor error"),
+        }
+    }
+
+    #[test]
+    fn vault_without_approvers_needs_no_proposal() {
+        let vault = approved_vault(&[], 0);
+        let action = ProposalAction::RevokeAccess { grantee: Pubkey::new_unique() };
+
+        require_approval(&vault, None, &action, vault.owner, 500).unwrap();
+    }
+
+    #[test]
+    fn stale_approvers_are_not_counted() {
+        let approvers = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let vault = approved_vault(&approvers, 2);
+        let action = ProposalAction::RevokeAccess { grantee: Pubkey::new_unique() };
+        let mut stale = proposal(&action, &[approvers[0], Pubkey::new_unique()]);
+
+        assert_eq!(stale.valid_approvals(&vault), 1);
+        assert_eq!(
+            error_code(require_approval(&vault, Some(&mut stale), &action, vault.owner, 500)),
+            u32::from(AgentMemoryError::InsufficientApprovals)
+        );
+        assert!(!stale.executed);
+
+        let mut approved = proposal(&action, &approvers);
+        require_approval(&vault, Some(&mut approved), &action, vault.owner, 500).unwrap();
+        assert!(approved.executed);
+    }
+
+    #[test]
+    fn proposal_must_match_and_be_unused() {
+        let approver = Pubkey::new_unique();
+        let vault = approved_vault(&[approver], 1);
+        let action = ProposalAction::RevokeAccess { grantee: Pubkey::new_unique() };
+        let other = ProposalAction::RevokeAccess { grantee: Pubkey::new_unique() };
+        let check = |proposal: Option<&mut Account<VaultProposal>>, now| {
+            error_code(require_approval(&vault, proposal, &action, vault.owner, now))
+        };
+
+        assert_eq!(check(None, 500), u32::from(AgentMemoryError::MultisigApprovalRequired));
+        assert_eq!(
+            check(Some(&mut proposal(&other, &[approver])), 500),
+            u32::from(AgentMemoryError::ProposalActionMismatch)
+        );
+        assert_eq!(
+            check(Some(&mut proposal(&action, &[approver])), 1_000),
+            u32::from(AgentMemoryError::ProposalExpired)
+        );
+        let mut executed = proposal(&action, &[approver]);
+        executed.executed = true;
+        assert_eq!(
+            check(Some(&mut executed), 500),
+            u32::from(AgentMemoryError::ProposalAlreadyExecuted)
+        );
+    }
+}
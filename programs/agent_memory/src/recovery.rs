@@ -0,0 +1,635 @@
+use anchor_lang::prelude::*;
+use crate::{
+    require_approval, AgentMemoryError, AgentProfile, MemoryVault, ProposalAction, VaultProposal,
+};
+
+// ============================================================================
+// SOCIAL RECOVERY MODULE
+// ============================================================================
+// Guardian-driven ownership recovery for vaults whose owner key is lost.
+// A guardian opens a request naming the new owner, other guardians approve,
+// and once the threshold is met the owner-veto delay starts; after it has
+// elapsed anyone may finalize the request. The current owner can cancel a
+// request at any time before finalization.
+//
+// Each guardian has its own request PDA, so a single guardian cannot block
+// recovery by holding a request open. Requests expire if they don't reach
+// the threshold, or aren't finalized, within `RECOVERY_REQUEST_WINDOW`, and
+// any ownership change invalidates all requests opened before it.
+
+/// Maximum number of guardians on a vault
+pub const MAX_GUARDIANS: usize = 10;
+
+/// Minimum delay between reaching the threshold and finalizing recovery (1 day)
+pub const MIN_RECOVERY_DELAY: i64 = 24 * 60 * 60;
+
+/// Maximum delay between reaching the threshold and finalizing recovery (30 days)
+pub const MAX_RECOVERY_DELAY: i64 = 30 * 24 * 60 * 60;
+
+/// Time a request has to reach the threshold, and then to be finalized once
+/// executable (7 days)
+pub const RECOVERY_REQUEST_WINDOW: i64 = 7 * 24 * 60 * 60;
+
+/// Pending recovery request for a vault
+#[account]
+#[derive(InitSpace)]
+pub struct RecoveryRequest {
+    /// The vault being recovered
+    pub vault: Pubkey,
+    /// Owner the vault is handed to on finalization
+    pub new_owner: Pubkey,
+    /// Guardian who opened the request (receives rent on close)
+    pub initiator: Pubkey,
+    /// Guardians who have approved
+    #[max_len(MAX_GUARDIANS)]
+    pub approvals: Vec<Pubkey>,
+    /// `MemoryVault::owner_epoch` when opened; the request dies with that owner
+    pub owner_epoch: u32,
+    /// Unix timestamp when recovery was initiated
+    pub initiated_at: i64,
+    /// Unix timestamp from which recovery may be finalized, set once the
+    /// guardian threshold is reached
+    pub executable_at: Option<i64>,
+    /// Unix timestamp after which the request can no longer be approved or finalized
+    pub expires_at: i64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl RecoveryRequest {
+    /// Number of approvals from keys that are still vault guardians
+    pub fn valid_approvals(&self, vault: &MemoryVault) -> usize {
+        self.approvals
+            .iter()
+            .filter(|guardian| vault.guardians.contains(guardian))
+            .count()
+    }
+
+    /// Whether the request can still be approved or finalized
+    pub fn is_live(&self, vault: &MemoryVault, now: i64) -> bool {
+        self.owner_epoch == vault.owner_epoch && now < self.expires_at
+    }
+
+    /// Starts the veto delay once the guardian threshold is first reached
+    fn start_delay_if_approved(&mut self, vault: &MemoryVault, now: i64) -> Result<()> {
+        if self.executable_at.is_none()
+            && vault.guardian_threshold > 0
+            && self.valid_approvals(vault) >= vault.guardian_threshold as usize
+        {
+            let executable_at = now
+                .checked_add(vault.recovery_delay)
+                .ok_or(AgentMemoryError::Overflow)?;
+            self.executable_at = Some(executable_at);
+            self.expires_at = executable_at
+                .checked_add(RECOVERY_REQUEST_WINDOW)
+                .ok_or(AgentMemoryError::Overflow)?;
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// INSTRUCTIONS
+// ============================================================================
+
+/// Configure recovery guardians, threshold and veto delay
+///
+/// An empty guardian set with a zero threshold disables recovery.
+pub fn set_recovery_guardians(
+    ctx: Context<SetRecoveryGuardians>,
+    guardians: Vec<Pubkey>,
+    threshold: u8,
+    delay: i64,
+) -> Result<()> {
+    let owner = ctx.accounts.owner.key();
+
+    require!(
+        guardians.len() <= MAX_GUARDIANS,
+        AgentMemoryError::TooManyGuardians
+    );
+    require!(
+        (threshold as usize) <= guardians.len() && (threshold == 0) == guardians.is_empty(),
+        AgentMemoryError::InvalidGuardianThreshold
+    );
+    for (i, guardian) in guardians.iter().enumerate() {
+        require!(
+            *guardian != owner && !guardians[..i].contains(guardian),
+            AgentMemoryError::InvalidGuardian
+        );
+    }
+    require!(
+        guardians.is_empty() || (MIN_RECOVERY_DELAY..=MAX_RECOVERY_DELAY).contains(&delay),
+        AgentMemoryError::InvalidRecoveryDelay
+    );
+
+    let clock = Clock::get()?;
+
+    // Guardians can take ownership, so changing them needs multisig approval
+    require_approval(
+        &ctx.accounts.vault,
+        ctx.accounts.proposal.as_mut(),
+        &ProposalAction::SetGuardians {
+            guardians: guardians.clone(),
+            threshold,
+            delay,
+        },
+        owner,
+        clock.unix_timestamp,
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    vault.guardians = guardians;
+    vault.guardian_threshold = threshold;
+    vault.recovery_delay = delay;
+    vault.updated_at = clock.unix_timestamp;
+    vault.last_heartbeat = clock.unix_timestamp;
+
+    emit!(RecoveryGuardiansUpdated {
+        vault: vault.key(),
+        guardians: vault.guardians.clone(),
+        threshold,
+        delay,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Open a recovery request naming a new owner
+pub fn initiate_recovery(ctx: Context<InitiateRecovery>, new_owner: Pubkey) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let guardian = ctx.accounts.guardian.key();
+    let clock = Clock::get()?;
+
+    require!(
+        vault.guardians.contains(&guardian),
+        AgentMemoryError::NotGuardian
+    );
+    require!(
+        new_owner != vault.owner && new_owner != Pubkey::default(),
+        AgentMemoryError::InvalidNewOwner
+    );
+
+    let request = &mut ctx.accounts.recovery_request;
+    request.vault = vault.key();
+    request.new_owner = new_owner;
+    request.initiator = guardian;
+    request.approvals = vec![guardian];
+    request.owner_epoch = vault.owner_epoch;
+    request.initiated_at = clock.unix_timestamp;
+    request.executable_at = None;
+    request.expires_at = clock
+        .unix_timestamp
+        .checked_add(RECOVERY_REQUEST_WINDOW)
+        .ok_or(AgentMemoryError::Overflow)?;
+    request.bump = ctx.bumps.recovery_request;
+    request.start_delay_if_approved(vault, clock.unix_timestamp)?;
+    vault.track_open_account()?;
+
+    emit!(RecoveryInitiated {
+        vault: vault.key(),
+        request: request.key(),
+        guardian,
+        new_owner,
+        executable_at: request.executable_at,
+        expires_at: request.expires_at,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Approve a pending recovery request
+///
+/// The approval that reaches the guardian threshold starts the veto delay.
+pub fn approve_recovery(ctx: Context<ApproveRecovery>) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    let request = &mut ctx.accounts.recovery_request;
+    let guardian = ctx.accounts.guardian.key();
+    let clock = Clock::get()?;
+
+    require!(
+        vault.guardians.contains(&guardian),
+        AgentMemoryError::NotGuardian
+    );
+    require!(
+        request.is_live(vault, clock.unix_timestamp),
+        AgentMemoryError::RecoveryExpired
+    );
+    require!(
+        !request.approvals.contains(&guardian),
+        AgentMemoryError::AlreadyApproved
+    );
+
+    request.approvals.push(guardian);
+    request.start_delay_if_approved(vault, clock.unix_timestamp)?;
+
+    emit!(RecoveryApproved {
+        vault: vault.key(),
+        request: request.key(),
+        guardian,
+        approvals: request.valid_approvals(vault) as u8,
+        executable_at: request.executable_at,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Finalize recovery once approved and the veto delay has elapsed
+pub fn finalize_recovery(ctx: Context<FinalizeRecovery>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let agent_profile = &mut ctx.accounts.agent_profile;
+    let request = &ctx.accounts.recovery_request;
+    let clock = Clock::get()?;
+
+    require!(
+        request.is_live(vault, clock.unix_timestamp),
+        AgentMemoryError::RecoveryExpired
+    );
+    require!(
+        vault.guardian_threshold > 0
+            && request.valid_approvals(vault) >= vault.guardian_threshold as usize,
+        AgentMemoryError::InsufficientApprovals
+    );
+    let executable_at = request
+        .executable_at
+        .ok_or(AgentMemoryError::InsufficientApprovals)?;
+    require!(
+        clock.unix_timestamp >= executable_at,
+        AgentMemoryError::RecoveryDelayActive
+    );
+
+    let old_owner = vault.owner;
+    vault.owner = request.new_owner;
+    vault.pending_owner = None;
+    vault.owner_epoch = vault
+        .owner_epoch
+        .checked_add(1)
+        .ok_or(AgentMemoryError::Overflow)?;
+    vault.updated_at = clock.unix_timestamp;
+    vault.last_heartbeat = clock.unix_timestamp;
+    vault.release_open_account();
+
+    agent_profile.owner = request.new_owner;
+    agent_profile.updated_at = clock.unix_timestamp;
+
+    emit!(RecoveryFinalized {
+        vault: vault.key(),
+        request: request.key(),
+        old_owner,
+        new_owner: request.new_owner,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Owner veto: cancel a pending recovery request
+pub fn cancel_recovery(ctx: Context<CancelRecovery>) -> Result<()> {
+    let request = &ctx.accounts.recovery_request;
+    let clock = Clock::get()?;
+
+    ctx.accounts.vault.last_heartbeat = clock.unix_timestamp;
+    ctx.accounts.vault.release_open_account();
+
+    emit!(RecoveryCancelled {
+        vault: request.vault,
+        request: request.key(),
+        cancelled_by: ctx.accounts.owner.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Close a recovery request that expired or was opened under a previous owner
+///
+/// Permissionless so that a guardian's stale request can be cleared even
+/// when the owner key is lost; rent returns to the initiator.
+pub fn expire_recovery(ctx: Context<ExpireRecovery>) -> Result<()> {
+    let request = &ctx.accounts.recovery_request;
+    let clock = Clock::get()?;
+
+    require!(
+        !request.is_live(&ctx.accounts.vault, clock.unix_timestamp),
+        AgentMemoryError::RecoveryStillOpen
+    );
+
+    ctx.accounts.vault.release_open_account();
+
+    emit!(RecoveryCancelled {
+        vault: request.vault,
+        request: request.key(),
+        cancelled_by: ctx.accounts.caller.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// ============================================================================
+// CONTEXT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct SetRecoveryGuardians<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+
+    /// Approved proposal, required when the vault has an approver set
+    #[account(
+        mut,
+        seeds = [b"proposal", vault.key().as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Option<Account<'info, VaultProposal>>,
+}
+
+#[derive(Accounts)]
+pub struct InitiateRecovery<'info> {
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        constraint = vault.migrated_to.is_none() @ AgentMemoryError::AlreadyMigrated,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+
+    #[account(
+        init,
+        payer = guardian,
+        space = 8 + RecoveryRequest::INIT_SPACE,
+        seeds = [b"recovery", vault.key().as_ref(), guardian.key().as_ref()],
+        bump
+    )]
+    pub recovery_request: Account<'info, RecoveryRequest>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveRecovery<'info> {
+    pub guardian: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+
+    #[account(
+        mut,
+        seeds = [b"recovery", vault.key().as_ref(), recovery_request.initiator.as_ref()],
+        bump = recovery_request.bump,
+        has_one = vault,
+    )]
+    pub recovery_request: Account<'info, RecoveryRequest>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeRecovery<'info> {
+    /// CHECK: Receives the request rent; must be the guardian who initiated it
+    #[account(mut)]
+    pub initiator: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+
+    #[account(
+        mut,
+        seeds = [b"profile", vault.agent_key.as_ref()],
+        bump = agent_profile.bump,
+        has_one = vault,
+    )]
+    pub agent_profile: Account<'info, AgentProfile>,
+
+    #[account(
+        mut,
+        close = initiator,
+        seeds = [b"recovery", vault.key().as_ref(), initiator.key().as_ref()],
+        bump = recovery_request.bump,
+        has_one = vault,
+        has_one = initiator,
+    )]
+    pub recovery_request: Account<'info, RecoveryRequest>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRecovery<'info> {
+    pub owner: Signer<'info>,
+
+    /// CHECK: Receives the request rent; must be the guardian who initiated it
+    #[account(mut)]
+    pub initiator: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+
+    #[account(
+        mut,
+        close = initiator,
+        seeds = [b"recovery", vault.key().as_ref(), initiator.key().as_ref()],
+        bump = recovery_request.bump,
+        has_one = vault,
+        has_one = initiator,
+    )]
+    pub recovery_request: Account<'info, RecoveryRequest>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireRecovery<'info> {
+    pub caller: Signer<'info>,
+
+    /// CHECK: Receives the request rent; must be the guardian who initiated it
+    #[account(mut)]
+    pub initiator: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+
+    #[account(
+        mut,
+        close = initiator,
+        seeds = [b"recovery", vault.key().as_ref(), initiator.key().as_ref()],
+        bump = recovery_request.bump,
+        has_one = vault,
+        has_one = initiator,
+    )]
+    pub recovery_request: Account<'info, RecoveryRequest>,
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[event]
+pub struct RecoveryGuardiansUpdated {
+    pub vault: Pubkey,
+    pub guardians: Vec<Pubkey>,
+    pub threshold: u8,
+    pub delay: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RecoveryInitiated {
+    pub vault: Pubkey,
+    pub request: Pubkey,
+    pub guardian: Pubkey,
+    pub new_owner: Pubkey,
+    pub executable_at: Option<i64>,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RecoveryApproved {
+    pub vault: Pubkey,
+    pub request: Pubkey,
+    pub guardian: Pubkey,
+    pub approvals: u8,
+    pub executable_at: Option<i64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RecoveryFinalized {
+    pub vault: Pubkey,
+    pub request: Pubkey,
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RecoveryCancelled {
+    pub vault: Pubkey,
+    pub request: Pubkey,
+    pub cancelled_by: Pubkey,
+    pub timestamp: i64,
+}
+
+// ============================================================================
+// SDK COMPATIBILITY HELPERS
+// ============================================================================
+
+/// Derive the PDA for a guardian's recovery request on a vault
+pub fn derive_recovery_pda(
+    vault: &Pubkey,
+    initiator: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"recovery",
+            vault.as_ref(),
+            initiator.as_ref(),
+        ],
+        program_id,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guarded_vault(guardians: &[Pubkey], threshold: u8) -> MemoryVault {
+        MemoryVault {
+            guardians: guardians.to_vec(),
+            guardian_threshold: threshold,
+            recovery_delay: 3_600,
+            ..crate::tests::vault()
+        }
+    }
+
+    fn request(vault: &MemoryVault, approvals: &[Pubkey]) -> RecoveryRequest {
+        RecoveryRequest {
+            vault: Pubkey::new_unique(),
+            new_owner: Pubkey::new_unique(),
+            initiator: approvals[0],
+            approvals: approvals.to_vec(),
+            owner_epoch: vault.owner_epoch,
+            initiated_at: 0,
+            executable_at: None,
+            expires_at: RECOVERY_REQUEST_WINDOW,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn delay_starts_when_threshold_is_reached() {
+        let guardians = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let vault = guarded_vault(&guardians, 2);
+
+        let mut pending = request(&vault, &guardians[..1]);
+        pending.start_delay_if_approved(&vault, 1_000).unwrap();
+        assert_eq!(pending.executable_at, None);
+        assert_eq!(pending.expires_at, RECOVERY_REQUEST_WINDOW);
+
+        let mut approved = request(&vault, &guardians[..2]);
+        approved.start_delay_if_approved(&vault, 1_000).unwrap();
+        assert_eq!(approved.executable_at, Some(4_600));
+        assert_eq!(approved.expires_at, 4_600 + RECOVERY_REQUEST_WINDOW);
+    }
+
+    #[test]
+    fn delay_is_not_restarted_by_later_approvals() {
+        let guardians = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let vault = guarded_vault(&guardians, 2);
+        let mut request = request(&vault, &guardians[..2]);
+
+        request.start_delay_if_approved(&vault, 1_000).unwrap();
+        request.approvals.push(guardians[2]);
+        request.start_delay_if_approved(&vault, 2_000).unwrap();
+
+        assert_eq!(request.executable_at, Some(4_600));
+    }
+
+    #[test]
+    fn removed_guardians_do_not_count() {
+        let guardians = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let vault = guarded_vault(&guardians, 2);
+        let mut request = request(&vault, &[guardians[0], Pubkey::new_unique()]);
+
+        request.start_delay_if_approved(&vault, 1_000).unwrap();
+
+        assert_eq!(request.valid_approvals(&vault), 1);
+        assert_eq!(request.executable_at, None);
+    }
+
+    #[test]
+    fn disabled_recovery_never_starts_delay() {
+        let guardian = Pubkey::new_unique();
+        let vault = guarded_vault(&[], 0);
+        let mut request = request(&vault, &[guardian]);
+
+        request.start_delay_if_approved(&vault, 1_000).unwrap();
+
+        assert_eq!(request.executable_at, None);
+    }
+}
@@ -0,0 +1,574 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use crate::{AgentMemoryError, MemoryVault, MAX_KEY_LENGTH};
+
+// ============================================================================
+// VAULT SNAPSHOT MODULE
+// ============================================================================
+// Checkpoints of a vault's memory state taken before risky operations.
+// A snapshot stores the vault counters and a running digest over
+// `(key, content_hash, version)` tuples supplied by the owner. Once every
+// entry is in, the owner seals the snapshot so the digest can no longer
+// change; the off-chain helpers in `diff` recompute that digest and diff a
+// live vault against it.
+
+/// Maximum snapshot label length
+pub const MAX_SNAPSHOT_LABEL_LENGTH: usize = 32;
+
+/// Vault snapshot account
+#[account]
+#[derive(InitSpace)]
+pub struct VaultSnapshot {
+    /// The vault this snapshot was taken of
+    pub vault: Pubkey,
+    /// Owner-chosen label, unique per vault
+    #[max_len(MAX_SNAPSHOT_LABEL_LENGTH)]
+    pub label: String,
+    /// Vault memory count when the snapshot was created
+    pub memory_count: u32,
+    /// Vault total memory size when the snapshot was created
+    pub total_memory_size: u64,
+    /// Number of entries folded into the digest
+    pub entry_count: u32,
+    /// Running digest over all supplied entries
+    pub digest: [u8; 32],
+    /// Unix timestamp when the snapshot was created
+    pub created_at: i64,
+    /// Unix timestamp of the last appended entries
+    pub updated_at: i64,
+    /// Set by `seal_vault_snapshot`; no further entries may be appended
+    pub sealed: bool,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+/// Shard state recorded in a snapshot
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotEntry {
+    pub key: String,
+    pub content_hash: [u8; 32],
+    pub version: u32,
+}
+
+/// Fold entries into a snapshot digest
+///
+/// Each entry extends the digest as
+/// `sha256(digest || len(key) || key || content_hash || version)`, so entries
+/// may be supplied across several transactions. Clients should supply entries
+/// sorted by key so the digest is reproducible.
+pub fn fold_snapshot_digest(mut digest: [u8; 32], entries: &[SnapshotEntry]) -> [u8; 32] {
+    for entry in entries {
+        digest = hashv(&[
+            &digest,
+            &(entry.key.len() as u32).to_le_bytes(),
+            entry.key.as_bytes(),
+            &entry.content_hash,
+            &entry.version.to_le_bytes(),
+        ])
+        .to_bytes();
+    }
+    digest
+}
+
+fn validate_entries(entries: &[SnapshotEntry]) -> Result<()> {
+    for entry in entries {
+        require!(!entry.key.is_empty(), AgentMemoryError::EmptyKey);
+        require!(
+            entry.key.len() <= MAX_KEY_LENGTH,
+            AgentMemoryError::KeyTooLong
+        );
+    }
+    Ok(())
+}
+
+// ============================================================================
+// INSTRUCTIONS
+// ============================================================================
+
+/// Create a snapshot of a vault
+pub fn create_vault_snapshot(
+    ctx: Context<CreateVaultSnapshot>,
+    label: String,
+    entries: Vec<SnapshotEntry>,
+) -> Result<()> {
+    require!(!label.is_empty(), AgentMemoryError::EmptySnapshotLabel);
+    require!(
+        label.len() <= MAX_SNAPSHOT_LABEL_LENGTH,
+        AgentMemoryError::SnapshotLabelTooLong
+    );
+    validate_entries(&entries)?;
+
+    let vault = &mut ctx.accounts.vault;
+    let snapshot = &mut ctx.accounts.snapshot;
+    let clock = Clock::get()?;
+
+    vault.track_open_account()?;
+    snapshot.vault = vault.key();
+    snapshot.label = label;
+    snapshot.memory_count = vault.memory_count;
+    snapshot.total_memory_size = vault.total_memory_size;
+    snapshot.entry_count = entries.len() as u32;
+    snapshot.digest = fold_snapshot_digest([0u8; 32], &entries);
+    snapshot.created_at = clock.unix_timestamp;
+    snapshot.updated_at = clock.unix_timestamp;
+    snapshot.sealed = false;
+    snapshot.bump = ctx.bumps.snapshot;
+
+    emit!(VaultSnapshotCreated {
+        vault: vault.key(),
+        snapshot: snapshot.key(),
+        label: snapshot.label.clone(),
+        memory_count: snapshot.memory_count,
+        total_memory_size: snapshot.total_memory_size,
+        entry_count: snapshot.entry_count,
+        digest: snapshot.digest,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Append further entries to a snapshot digest
+pub fn extend_vault_snapshot(
+    ctx: Context<ExtendVaultSnapshot>,
+    entries: Vec<SnapshotEntry>,
+) -> Result<()> {
+    require!(!entries.is_empty(), AgentMemoryError::EmptyBatch);
+    validate_entries(&entries)?;
+
+    let snapshot = &mut ctx.accounts.snapshot;
+    let clock = Clock::get()?;
+
+    require!(!snapshot.sealed, AgentMemoryError::SnapshotSealed);
+
+    snapshot.entry_count = snapshot
+        .entry_count
+        .checked_add(entries.len() as u32)
+        .ok_or(AgentMemoryError::Overflow)?;
+    snapshot.digest = fold_snapshot_digest(snapshot.digest, &entries);
+    snapshot.updated_at = clock.unix_timestamp;
+
+    emit!(VaultSnapshotExtended {
+        vault: snapshot.vault,
+        snapshot: snapshot.key(),
+        entry_count: snapshot.entry_count,
+        digest: snapshot.digest,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Seal a snapshot once all of its entries have been appended
+pub fn seal_vault_snapshot(ctx: Context<ExtendVaultSnapshot>) -> Result<()> {
+    let snapshot = &mut ctx.accounts.snapshot;
+    let clock = Clock::get()?;
+
+    require!(!snapshot.sealed, AgentMemoryError::SnapshotSealed);
+
+    snapshot.sealed = true;
+    snapshot.updated_at = clock.unix_timestamp;
+
+    emit!(VaultSnapshotSealed {
+        vault: snapshot.vault,
+        snapshot: snapshot.key(),
+        entry_count: snapshot.entry_count,
+        digest: snapshot.digest,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Close a snapshot and return its rent to the owner
+pub fn close_vault_snapshot(ctx: Context<CloseVaultSnapshot>) -> Result<()> {
+    let snapshot = &ctx.accounts.snapshot;
+    let clock = Clock::get()?;
+
+    ctx.accounts.vault.release_open_account();
+
+    emit!(VaultSnapshotClosed {
+        vault: snapshot.vault,
+        snapshot: snapshot.key(),
+        label: snapshot.label.clone(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// ============================================================================
+// CONTEXT STRUCTURES
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(label: String)]
+pub struct CreateVaultSnapshot<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + VaultSnapshot::INIT_SPACE,
+        seeds = [b"snapshot", vault.key().as_ref(), label.as_bytes()],
+        bump
+    )]
+    pub snapshot: Account<'info, VaultSnapshot>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendVaultSnapshot<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+
+    #[account(
+        mut,
+        seeds = [b"snapshot", vault.key().as_ref(), snapshot.label.as_bytes()],
+        bump = snapshot.bump,
+        has_one = vault,
+    )]
+    pub snapshot: Account<'info, VaultSnapshot>,
+}
+
+#[derive(Accounts)]
+pub struct CloseVaultSnapshot<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.seed_owner.as_ref(), vault.agent_key.as_ref()],
+        bump = vault.bump,
+        has_one = owner @ AgentMemoryError::UnauthorizedOwner,
+    )]
+    pub vault: Account<'info, MemoryVault>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"snapshot", vault.key().as_ref(), snapshot.label.as_bytes()],
+        bump = snapshot.bump,
+        has_one = vault,
+    )]
+    pub snapshot: Account<'info, VaultSnapshot>,
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[event]
+pub struct VaultSnapshotCreated {
+    pub vault: Pubkey,
+    pub snapshot: Pubkey,
+    pub label: String,
+    pub memory_count: u32,
+    pub total_memory_size: u64,
+    pub entry_count: u32,
+    pub digest: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultSnapshotExtended {
+    pub vault: Pubkey,
+    pub snapshot: Pubkey,
+    pub entry_count: u32,
+    pub digest: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultSnapshotSealed {
+    pub vault: Pubkey,
+    pub snapshot: Pubkey,
+    pub entry_count: u32,
+    pub digest: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultSnapshotClosed {
+    pub vault: Pubkey,
+    pub snapshot: Pubkey,
+    pub label: String,
+    pub timestamp: i64,
+}
+
+// ============================================================================
+// SDK COMPATIBILITY HELPERS
+// ============================================================================
+
+/// Derive the PDA for a vault snapshot
+pub fn derive_snapshot_pda(
+    vault: &Pubkey,
+    label: &str,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"snapshot",
+            vault.as_ref(),
+            label.as_bytes(),
+        ],
+        program_id,
+    )
+}
+
+// ============================================================================
+// OFF-CHAIN DIFF TOOLING
+// ============================================================================
+// Used by clients to compare a live vault against a snapshot and plan the
+// restores and rollbacks needed to return to it.
+
+/// Snapshot verification and diffing for clients (enable the `client` feature)
+#[cfg(feature = "client")]
+pub mod diff {
+    use super::{fold_snapshot_digest, SnapshotEntry, VaultSnapshot};
+    use std::collections::BTreeMap;
+
+    /// Live state of a memory shard as fetched from the cluster
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct LiveShard {
+        pub key: String,
+        pub content_hash: [u8; 32],
+        pub version: u32,
+        pub is_deleted: bool,
+    }
+
+    /// Step needed to bring a shard back to its snapshot state
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub enum RollbackAction {
+        /// Content changed since the snapshot; roll back to `to_version`
+        Rollback { key: String, to_version: u32 },
+        /// Content differs but the live version is not newer than the
+        /// snapshot's, so there is no version to roll back to
+        Conflict { key: String },
+        /// Soft-deleted since the snapshot; restore, then roll back if
+        /// `to_version` differs from the live version
+        Restore { key: String, to_version: u32 },
+        /// Created after the snapshot; soft-delete it
+        Delete { key: String },
+        /// Permanently deleted since the snapshot; cannot be recovered on-chain
+        Missing { key: String },
+    }
+
+    /// Sort entries by key, the order the digest is expected to be built in
+    pub fn sort_snapshot_entries(entries: &mut [SnapshotEntry]) {
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+    }
+
+    /// Check that `entries` reproduce the digest recorded in a sealed `snapshot`
+    pub fn verify_snapshot_entries(snapshot: &VaultSnapshot, entries: &[SnapshotEntry]) -> bool {
+        snapshot.sealed
+            && entries.len() == snapshot.entry_count as usize
+            && fold_snapshot_digest([0u8; 32], entries) == snapshot.digest
+    }
+
+    /// Diff a live vault against snapshot entries
+    ///
+    /// Returns the actions needed to return the vault to the snapshot, sorted
+    /// by key. Shards identical to the snapshot produce no action; shards
+    /// whose content diverged without a newer version are reported as
+    /// conflicts rather than rollbacks.
+    pub fn diff_vault_snapshot(
+        snapshot_entries: &[SnapshotEntry],
+        live_shards: &[LiveShard],
+    ) -> Vec<RollbackAction> {
+        let live: BTreeMap<&str, &LiveShard> = live_shards
+            .iter()
+            .map(|shard| (shard.key.as_str(), shard))
+            .collect();
+        let recorded: BTreeMap<&str, &SnapshotEntry> = snapshot_entries
+            .iter()
+            .map(|entry| (entry.key.as_str(), entry))
+            .collect();
+
+        let mut actions = Vec::new();
+        for (key, entry) in &recorded {
+            let key = key.to_string();
+            match live.get(entry.key.as_str()) {
+                None => actions.push(RollbackAction::Missing { key }),
+                Some(shard) if shard.is_deleted => actions.push(RollbackAction::Restore {
+                    key,
+                    to_version: entry.version,
+                }),
+                Some(shard) if shard.version > entry.version => {
+                    actions.push(RollbackAction::Rollback {
+                        key,
+                        to_version: entry.version,
+                    })
+                }
+                Some(shard) if shard.content_hash != entry.content_hash
+                    || shard.version != entry.version =>
+                {
+                    actions.push(RollbackAction::Conflict { key })
+                }
+                Some(_) => {}
+            }
+        }
+        for (key, shard) in &live {
+            if !shard.is_deleted && !recorded.contains_key(key) {
+                actions.push(RollbackAction::Delete { key: key.to_string() });
+            }
+        }
+
+        actions.sort_by(|a, b| action_key(a).cmp(action_key(b)));
+        actions
+    }
+
+    fn action_key(action: &RollbackAction) -> &str {
+        match action {
+            RollbackAction::Rollback { key, .. }
+            | RollbackAction::Conflict { key }
+            | RollbackAction::Restore { key, .. }
+            | RollbackAction::Delete { key }
+            | RollbackAction::Missing { key } => key,
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn entry(key: &str, hash: u8, version: u32) -> SnapshotEntry {
+            SnapshotEntry {
+                key: key.to_string(),
+                content_hash: [hash; 32],
+                version,
+            }
+        }
+
+        fn live(key: &str, hash: u8, version: u32, is_deleted: bool) -> LiveShard {
+            LiveShard {
+                key: key.to_string(),
+                content_hash: [hash; 32],
+                version,
+                is_deleted,
+            }
+        }
+
+        fn snapshot_of(entries: &[SnapshotEntry]) -> VaultSnapshot {
+            VaultSnapshot {
+                vault: Default::default(),
+                label: "test".to_string(),
+                memory_count: entries.len() as u32,
+                total_memory_size: 0,
+                entry_count: entries.len() as u32,
+                digest: fold_snapshot_digest([0u8; 32], entries),
+                created_at: 0,
+                updated_at: 0,
+                sealed: true,
+                bump: 255,
+            }
+        }
+
+        #[test]
+        fn unchanged_vault_has_no_actions() {
+            let entries = [entry("a", 1, 1), entry("b", 2, 3)];
+            let shards = [live("b", 2, 3, false), live("a", 1, 1, false)];
+
+            assert!(diff_vault_snapshot(&entries, &shards).is_empty());
+        }
+
+        #[test]
+        fn newer_version_rolls_back() {
+            let entries = [entry("a", 1, 2)];
+
+            assert_eq!(
+                diff_vault_snapshot(&entries, &[live("a", 9, 4, false)]),
+                vec![RollbackAction::Rollback { key: "a".to_string(), to_version: 2 }]
+            );
+        }
+
+        #[test]
+        fn same_version_with_other_content_conflicts() {
+            let entries = [entry("a", 1, 2)];
+
+            assert_eq!(
+                diff_vault_snapshot(&entries, &[live("a", 9, 2, false)]),
+                vec![RollbackAction::Conflict { key: "a".to_string() }]
+            );
+        }
+
+        #[test]
+        fn older_version_conflicts() {
+            let entries = [entry("a", 1, 5)];
+
+            assert_eq!(
+                diff_vault_snapshot(&entries, &[live("a", 1, 2, false)]),
+                vec![RollbackAction::Conflict { key: "a".to_string() }]
+            );
+        }
+
+        #[test]
+        fn reports_restores_deletes_and_missing_in_key_order() {
+            let entries = [entry("b", 1, 2), entry("d", 1, 1)];
+            let shards = [
+                live("c", 3, 1, false),
+                live("b", 1, 3, true),
+                live("a", 4, 1, true),
+            ];
+
+            assert_eq!(
+                diff_vault_snapshot(&entries, &shards),
+                vec![
+                    RollbackAction::Restore { key: "b".to_string(), to_version: 2 },
+                    RollbackAction::Delete { key: "c".to_string() },
+                    RollbackAction::Missing { key: "d".to_string() },
+                ]
+            );
+        }
+
+        #[test]
+        fn verifies_sorted_entries_only() {
+            let mut entries = vec![entry("b", 2, 1), entry("a", 1, 1)];
+            sort_snapshot_entries(&mut entries);
+            let snapshot = snapshot_of(&entries);
+
+            assert!(verify_snapshot_entries(&snapshot, &entries));
+
+            entries.reverse();
+            assert!(!verify_snapshot_entries(&snapshot, &entries));
+            assert!(!verify_snapshot_entries(&snapshot, &entries[..1]));
+        }
+
+        #[test]
+        fn unsealed_snapshot_does_not_verify() {
+            let entries = [entry("a", 1, 1)];
+            let mut snapshot = snapshot_of(&entries);
+            snapshot.sealed = false;
+
+            assert!(!verify_snapshot_entries(&snapshot, &entries));
+        }
+
+        #[test]
+        fn digest_folds_incrementally() {
+            let entries = [entry("a", 1, 1), entry("b", 2, 1), entry("c", 3, 1)];
+            let partial = fold_snapshot_digest([0u8; 32], &entries[..1]);
+
+            assert_eq!(
+                fold_snapshot_digest(partial, &entries[1..]),
+                fold_snapshot_digest([0u8; 32], &entries)
+            );
+        }
+    }
+}